@@ -0,0 +1,307 @@
+//! Sprite sheet animation.
+//!
+//! An `Animation` is a sequence of frames in a texture,
+//! each shown for a duration in seconds.
+//! Frames can be laid out in a uniform grid,
+//! or packed into texture atlases with `TexturePacker`.
+//!
+//! ```
+//! use graphics::{animation::{Animation, Playback}, software::{Canvas, Texture}, Context, Image};
+//!
+//! // Four frames of 16x16 pixels in a row.
+//! let texture = Texture::from_rgba8(64, 16, vec![255; 64 * 16 * 4]).unwrap();
+//! let animation = Animation::grid([0.0, 0.0, 64.0, 16.0], 4, 1, 0.1).playback(Playback::PingPong);
+//! let c = Context::new_abs(100.0, 100.0);
+//! let mut canvas = Canvas::new(100, 100);
+//! let image = Image::new().rect([10.0, 10.0, 32.0, 32.0]);
+//! animation.draw(0.25, &image, &[texture], &c.draw_state, c.transform, &mut canvas);
+//! ```
+
+use crate::{
+    math::{Matrix2d, Scalar},
+    types::SourceRectangle,
+    DrawState, Graphics, Image,
+};
+
+/// How an animation continues after the last frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Playback {
+    /// Stops at the last frame.
+    Once,
+    /// Starts over from the first frame.
+    Loop,
+    /// Plays backwards to the first frame, then forwards again.
+    PingPong,
+}
+
+/// A frame of an animation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// The index of the texture atlas containing the frame.
+    pub atlas: usize,
+    /// The source rectangle of the frame.
+    pub source_rectangle: SourceRectangle,
+    /// How long the frame is shown, in seconds.
+    pub duration: Scalar,
+}
+
+/// A sequence of frames with durations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    /// The frames, in order.
+    pub frames: Vec<Frame>,
+    /// How the animation continues after the last frame.
+    pub playback: Playback,
+}
+
+impl Animation {
+    /// Creates a new looping animation without frames.
+    pub fn new() -> Animation {
+        Animation {
+            frames: vec![],
+            playback: Playback::Loop,
+        }
+    }
+
+    /// Creates a new looping animation from a uniform grid of frames.
+    ///
+    /// The area is split into columns and rows of equally sized frames,
+    /// ordered from left to right and then from top to bottom.
+    pub fn grid(area: SourceRectangle, columns: u32, rows: u32, duration: Scalar) -> Animation {
+        let (w, h) = (area[2] / columns as Scalar, area[3] / rows as Scalar);
+        let mut animation = Animation::new();
+        for row in 0..rows {
+            for col in 0..columns {
+                let (x, y) = (area[0] + col as Scalar * w, area[1] + row as Scalar * h);
+                animation = animation.frame([x, y, w, h], duration);
+            }
+        }
+        animation
+    }
+
+    /// Adds a frame in the first texture atlas.
+    pub fn frame(self, source_rectangle: SourceRectangle, duration: Scalar) -> Self {
+        self.atlas_frame(0, source_rectangle, duration)
+    }
+
+    /// Adds a frame in a texture atlas.
+    pub fn atlas_frame(
+        mut self,
+        atlas: usize,
+        source_rectangle: SourceRectangle,
+        duration: Scalar,
+    ) -> Self {
+        self.frames.push(Frame {
+            atlas,
+            source_rectangle,
+            duration,
+        });
+        self
+    }
+
+    /// Adds a frame packed with `TexturePacker`.
+    ///
+    /// `packed` is the atlas index and offset returned by `TexturePacker::update`.
+    pub fn packed_frame(self, packed: (usize, [u32; 2]), size: [u32; 2], duration: Scalar) -> Self {
+        let (atlas, offset) = packed;
+        self.atlas_frame(
+            atlas,
+            [
+                offset[0] as Scalar,
+                offset[1] as Scalar,
+                size[0] as Scalar,
+                size[1] as Scalar,
+            ],
+            duration,
+        )
+    }
+
+    /// Sets how the animation continues after the last frame.
+    pub fn playback(mut self, value: Playback) -> Self {
+        self.playback = value;
+        self
+    }
+
+    /// Returns the duration of playing all frames once, in seconds.
+    pub fn duration(&self) -> Scalar {
+        self.frames.iter().map(|f| f.duration).sum()
+    }
+
+    /// Returns the index of the frame shown at a time in seconds.
+    ///
+    /// Times before the start show the first frame.
+    /// Returns `None` when there are no frames.
+    pub fn frame_index_at(&self, time: Scalar) -> Option<usize> {
+        let n = self.frames.len();
+        if n == 0 {
+            return None;
+        }
+        let total = self.duration();
+        if n == 1 || total <= 0.0 || time <= 0.0 {
+            return Some(0);
+        }
+        // Looks up a frame by time from the start of a sequence of indices.
+        let find = |mut time: Scalar, indices: &mut dyn Iterator<Item = usize>| {
+            for i in indices {
+                if time < self.frames[i].duration {
+                    return Some(i);
+                }
+                time -= self.frames[i].duration;
+            }
+            None
+        };
+        match self.playback {
+            Playback::Once => find(time, &mut (0..n)).or(Some(n - 1)),
+            Playback::Loop => find(time % total, &mut (0..n)).or(Some(n - 1)),
+            Playback::PingPong => {
+                // The first and last frames are not repeated when turning.
+                let back: Scalar = self.frames[1..n - 1].iter().map(|f| f.duration).sum();
+                let time = time % (total + back);
+                find(time, &mut (0..n).chain((1..n - 1).rev())).or(Some(0))
+            }
+        }
+    }
+
+    /// Returns the frame shown at a time in seconds.
+    pub fn frame_at(&self, time: Scalar) -> Option<&Frame> {
+        self.frame_index_at(time).map(|i| &self.frames[i])
+    }
+
+    /// Draws the frame shown at a time in seconds.
+    ///
+    /// The source rectangle of the image is set to the frame,
+    /// and the texture is looked up by the atlas index of the frame.
+    /// With `TexturePacker`, pass `&packer.textures`.
+    ///
+    /// Panics if the atlas of the frame is not in `textures`.
+    pub fn draw<G>(
+        &self,
+        time: Scalar,
+        image: &Image,
+        textures: &[<G as Graphics>::Texture],
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        if let Some(frame) = self.frame_at(time) {
+            image.src_rect(frame.source_rectangle).draw(
+                &textures[frame.atlas],
+                draw_state,
+                transform,
+                g,
+            );
+        }
+    }
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Animation::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let animation = Animation::grid([0.0, 8.0, 32.0, 16.0], 4, 2, 0.5);
+        assert_eq!(animation.frames.len(), 8);
+        assert_eq!(animation.frames[1].source_rectangle, [8.0, 8.0, 8.0, 8.0]);
+        assert_eq!(animation.frames[4].source_rectangle, [0.0, 16.0, 8.0, 8.0]);
+        assert_eq!(animation.duration(), 4.0);
+
+        let packed = Animation::new().packed_frame((1, [4, 2]), [8, 8], 0.1);
+        assert_eq!(packed.frames[0].atlas, 1);
+        assert_eq!(packed.frames[0].source_rectangle, [4.0, 2.0, 8.0, 8.0]);
+    }
+
+    #[test]
+    fn test_playback() {
+        let animation = Animation::new()
+            .frame([0.0; 4], 1.0)
+            .frame([1.0; 4], 2.0)
+            .frame([2.0; 4], 1.0);
+        let indices = |animation: &Animation| {
+            [-1.0, 0.5, 1.5, 3.5, 4.5, 6.5, 7.5, 8.5]
+                .iter()
+                .map(|&t| animation.frame_index_at(t).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(indices(&animation), vec![0, 0, 1, 2, 0, 1, 2, 0]);
+        let once = animation.clone().playback(Playback::Once);
+        assert_eq!(indices(&once), vec![0, 0, 1, 2, 2, 2, 2, 2]);
+        let ping_pong = animation.playback(Playback::PingPong);
+        assert_eq!(indices(&ping_pong), vec![0, 0, 1, 2, 1, 0, 1, 1]);
+        assert_eq!(Animation::new().frame_index_at(1.0), None);
+    }
+
+    #[test]
+    fn test_draw() {
+        use crate::{
+            software::{Canvas, Texture},
+            Context,
+        };
+
+        // Red frame and blue frame.
+        let textures = [Texture::from_rgba8(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 255]).unwrap()];
+        let animation = Animation::grid([0.0, 0.0, 2.0, 1.0], 2, 1, 1.0);
+        let c = Context::new_abs(4.0, 4.0);
+        let image = Image::new().rect([0.0, 0.0, 4.0, 4.0]);
+        let mut canvas = Canvas::new(4, 4);
+        animation.draw(
+            1.5,
+            &image,
+            &textures,
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(0, 0), [0, 0, 255, 255]);
+        animation.draw(
+            2.5,
+            &image,
+            &textures,
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(3, 3), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_draw_atlases() {
+        use crate::{
+            software::{Canvas, Texture},
+            Context,
+        };
+
+        // Red and green frames in the first atlas, a blue frame in the second one.
+        let textures = [
+            Texture::from_rgba8(2, 1, vec![255, 0, 0, 255, 0, 255, 0, 255]).unwrap(),
+            Texture::from_rgba8(1, 1, vec![0, 0, 255, 255]).unwrap(),
+        ];
+        let animation = Animation::new()
+            .packed_frame((0, [1, 0]), [1, 1], 1.0)
+            .packed_frame((1, [0, 0]), [1, 1], 1.0);
+        let c = Context::new_abs(4.0, 4.0);
+        let image = Image::new().rect([0.0, 0.0, 4.0, 4.0]);
+        let draw = |time| {
+            let mut canvas = Canvas::new(4, 4);
+            animation.draw(
+                time,
+                &image,
+                &textures,
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+            canvas.pixel(2, 2)
+        };
+        assert_eq!(draw(0.5), [0, 255, 0, 255]);
+        assert_eq!(draw(1.5), [0, 0, 255, 255]);
+    }
+}
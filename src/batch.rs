@@ -0,0 +1,265 @@
+//! Automatic batching of draw calls.
+//!
+//! Drawing many small shapes, e.g. with `Rectangle::draw`,
+//! issues one back-end call per shape.
+//! `Batch` wraps a back-end and merges consecutive `tri_list` calls
+//! with same draw state and color into one call.
+//! Consecutive `tri_list_c` calls with same draw state are merged too.
+//! Textured calls are forwarded to the back-end after sending the pending vertices,
+//! since textures can not be compared without support from the back-end.
+//!
+//! The vertices are sent to the back-end when the settings change,
+//! when `BACK_END_MAX_VERTEX_COUNT` is reached, on `Batch::flush`
+//! and when the batch is dropped.
+//!
+//! ```
+//! use graphics::{batch::Batch, display_list::DisplayList, software::Texture, Context, Rectangle};
+//!
+//! let c = Context::new_abs(100.0, 100.0);
+//! let mut list: DisplayList<Texture> = DisplayList::new();
+//! {
+//!     let mut g = Batch::new(&mut list);
+//!     for i in 0..10 {
+//!         let x = i as f64 * 10.0;
+//!         Rectangle::new([1.0; 4]).draw([x, 0.0, 5.0, 5.0], &c.draw_state, c.transform, &mut g);
+//!     }
+//! }
+//! assert_eq!(list.commands.len(), 1);
+//! ```
+
+use crate::{types::Color, DrawState, Graphics, BACK_END_MAX_VERTEX_COUNT};
+
+/// The draw call waiting for more vertices.
+enum Pending {
+    None,
+    TriList { draw_state: DrawState, color: Color },
+    TriListC { draw_state: DrawState },
+}
+
+/// Merges consecutive draw calls with same settings.
+pub struct Batch<'a, G: Graphics> {
+    g: &'a mut G,
+    pending: Pending,
+    vertices: Vec<[f32; 2]>,
+    colors: Vec<[f32; 4]>,
+}
+
+impl<'a, G> Batch<'a, G>
+where
+    G: Graphics,
+{
+    /// Creates a new batch wrapping a back-end.
+    pub fn new(g: &'a mut G) -> Batch<'a, G> {
+        Batch {
+            g,
+            pending: Pending::None,
+            vertices: Vec::with_capacity(BACK_END_MAX_VERTEX_COUNT),
+            colors: Vec::with_capacity(BACK_END_MAX_VERTEX_COUNT),
+        }
+    }
+
+    /// Sends the pending vertices to the back-end.
+    pub fn flush(&mut self) {
+        let vertices = &self.vertices;
+        let colors = &self.colors;
+        match std::mem::replace(&mut self.pending, Pending::None) {
+            Pending::None => return,
+            Pending::TriList { draw_state, color } => {
+                self.g.tri_list(&draw_state, &color, |f| f(vertices));
+            }
+            Pending::TriListC { draw_state } => {
+                self.g.tri_list_c(&draw_state, |f| f(vertices, colors));
+            }
+        }
+        self.vertices.clear();
+        self.colors.clear();
+    }
+}
+
+impl<'a, G> Drop for Batch<'a, G>
+where
+    G: Graphics,
+{
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<'a, G> Graphics for Batch<'a, G>
+where
+    G: Graphics,
+{
+    type Texture = G::Texture;
+
+    fn clear_color(&mut self, color: Color) {
+        self.flush();
+        self.g.clear_color(color);
+    }
+
+    fn clear_stencil(&mut self, value: u8) {
+        self.flush();
+        self.g.clear_stencil(value);
+    }
+
+    fn tri_list<F>(&mut self, draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+        match self.pending {
+            Pending::TriList {
+                draw_state: ref ds,
+                color: ref c,
+            } if ds == draw_state && c == color => {}
+            _ => {
+                self.flush();
+                self.pending = Pending::TriList {
+                    draw_state: *draw_state,
+                    color: *color,
+                };
+            }
+        }
+        let g = &mut *self.g;
+        let buffer = &mut self.vertices;
+        f(&mut |vertices| {
+            if buffer.len() + vertices.len() > BACK_END_MAX_VERTEX_COUNT {
+                g.tri_list(draw_state, color, |f| f(buffer));
+                buffer.clear();
+            }
+            buffer.extend_from_slice(vertices);
+        });
+    }
+
+    fn tri_list_c<F>(&mut self, draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        match self.pending {
+            Pending::TriListC { draw_state: ref ds } if ds == draw_state => {}
+            _ => {
+                self.flush();
+                self.pending = Pending::TriListC {
+                    draw_state: *draw_state,
+                };
+            }
+        }
+        let g = &mut *self.g;
+        let buffer = &mut self.vertices;
+        let color_buffer = &mut self.colors;
+        f(&mut |vertices, colors| {
+            if buffer.len() + vertices.len() > BACK_END_MAX_VERTEX_COUNT {
+                g.tri_list_c(draw_state, |f| f(buffer, color_buffer));
+                buffer.clear();
+                color_buffer.clear();
+            }
+            buffer.extend_from_slice(vertices);
+            color_buffer.extend_from_slice(colors);
+        });
+    }
+
+    fn tri_list_uv<F>(
+        &mut self,
+        draw_state: &DrawState,
+        color: &[f32; 4],
+        texture: &G::Texture,
+        f: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+        self.flush();
+        self.g.tri_list_uv(draw_state, color, texture, f);
+    }
+
+    fn tri_list_uv_c<F>(&mut self, draw_state: &DrawState, texture: &G::Texture, f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+        self.flush();
+        self.g.tri_list_uv_c(draw_state, texture, f);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        display_list::{Command, DisplayList},
+        gradient::Gradient,
+        software::Texture,
+        Context, Image, Rectangle,
+    };
+
+    #[test]
+    fn test_batch() {
+        let c = Context::new_abs(100.0, 100.0);
+        let mut list: DisplayList<Texture> = DisplayList::new();
+        {
+            let mut g = Batch::new(&mut list);
+            // 6 vertices per rectangle, 2 chunks.
+            for _ in 0..200 {
+                Rectangle::new([1.0; 4]).draw(
+                    [0.0, 0.0, 5.0, 5.0],
+                    &c.draw_state,
+                    c.transform,
+                    &mut g,
+                );
+            }
+            Rectangle::new([0.5; 4]).draw([0.0, 0.0, 5.0, 5.0], &c.draw_state, c.transform, &mut g);
+        }
+        assert_eq!(list.commands.len(), 3);
+        match &list.commands[0] {
+            Command::TriList { vertices, .. } => assert_eq!(vertices.len(), 1020),
+            _ => panic!("Expected tri list"),
+        }
+    }
+
+    #[test]
+    fn test_batch_colors() {
+        let c = Context::new_abs(100.0, 100.0);
+        let mut list: DisplayList<Texture> = DisplayList::new();
+        {
+            let mut g = Batch::new(&mut list);
+            let gradient = Gradient::linear([0.0; 2], [5.0; 2])
+                .stop(0.0, [1.0; 4])
+                .stop(1.0, [0.0; 4]);
+            let rectangle = Rectangle::new([1.0; 4]);
+            for _ in 0..3 {
+                rectangle.draw_gradient(
+                    &gradient,
+                    [0.0, 0.0, 5.0, 5.0],
+                    &c.draw_state,
+                    c.transform,
+                    &mut g,
+                );
+            }
+        }
+        assert_eq!(list.commands.len(), 1);
+        match &list.commands[0] {
+            Command::TriListC {
+                vertices, colors, ..
+            } => assert_eq!(vertices.len(), colors.len()),
+            _ => panic!("Expected colored tri list"),
+        }
+    }
+
+    #[test]
+    fn test_batch_texture() {
+        let c = Context::new_abs(100.0, 100.0);
+        let texture = Texture::from_rgba8(1, 1, vec![255; 4]).unwrap();
+        let mut list: DisplayList<Texture> = DisplayList::new();
+        {
+            let mut g = Batch::new(&mut list);
+            let rectangle = Rectangle::new([1.0; 4]);
+            let image = Image::new().rect([0.0, 0.0, 5.0, 5.0]);
+            rectangle.draw([0.0, 0.0, 5.0, 5.0], &c.draw_state, c.transform, &mut g);
+            // Textured calls are not merged, but keep their order.
+            image.draw(&texture, &c.draw_state, c.transform, &mut g);
+            image.draw(&texture, &c.draw_state, c.transform, &mut g);
+            rectangle.draw([0.0, 0.0, 5.0, 5.0], &c.draw_state, c.transform, &mut g);
+        }
+        assert_eq!(list.commands.len(), 4);
+        match (&list.commands[0], &list.commands[1], &list.commands[3]) {
+            (Command::TriList { .. }, Command::TriListUv { .. }, Command::TriList { .. }) => {}
+            _ => panic!("Expected textured tri lists between tri lists"),
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Measuring the bounds of what is drawn.
+//!
+//! `Bounds` implements `Graphics` by accumulating the axis-aligned bounding box
+//! of all vertices, without drawing anything.
+//! `Forward` does the same while forwarding the draw calls to another back-end.
+//!
+//! This can be used to compute the size of existing drawing code,
+//! e.g. to lay out composite drawings of text and shapes.
+//!
+//! Vertices passed to a back-end are transformed.
+//! Use `Bounds::new_transform` to compute the bounds in the coordinates
+//! before the transform was applied.
+//!
+//! ```
+//! use graphics::{bounds::Bounds, software::Texture, Context, Ellipse, Rectangle};
+//!
+//! let c = Context::new_abs(100.0, 100.0);
+//! let mut bounds: Bounds<Texture> = Bounds::new_transform(c.transform);
+//! Rectangle::new([1.0; 4]).draw([10.0, 10.0, 20.0, 20.0], &c.draw_state, c.transform, &mut bounds);
+//! Ellipse::new([1.0; 4]).draw([20.0, 20.0, 20.0, 30.0], &c.draw_state, c.transform, &mut bounds);
+//! let rect = bounds.rect().unwrap();
+//! assert!((rect[0] - 10.0).abs() < 0.001);
+//! assert!((rect[3] - 40.0).abs() < 0.001);
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::{
+    draw_state::Stencil,
+    math::{identity, invert, transform_pos, Matrix2d, Scalar},
+    types::{Color, Rectangle},
+    DrawState, Graphics, ImageSize, VertexIndex,
+};
+
+/// Accumulates the bounding box of vertices without drawing anything.
+///
+/// Shapes drawn only to the stencil buffer are not visible,
+/// so they are not included.
+#[derive(Clone, Debug)]
+pub struct Bounds<T> {
+    inverse: Matrix2d,
+    min: [Scalar; 2],
+    max: [Scalar; 2],
+    texture: PhantomData<T>,
+}
+
+impl<T> Bounds<T> {
+    /// Creates new empty bounds in the coordinates used by the back-end.
+    pub fn new() -> Bounds<T> {
+        Bounds::new_transform(identity())
+    }
+
+    /// Creates new empty bounds in coordinates before transform.
+    ///
+    /// Since every vertex is transformed back,
+    /// the bounds are tight even when the transform contains rotation.
+    pub fn new_transform(transform: Matrix2d) -> Bounds<T> {
+        Bounds {
+            inverse: invert(transform),
+            min: [Scalar::INFINITY; 2],
+            max: [Scalar::NEG_INFINITY; 2],
+            texture: PhantomData,
+        }
+    }
+
+    /// Returns the bounding rectangle `[x, y, w, h]`.
+    ///
+    /// Returns `None` if no vertices were drawn.
+    pub fn rect(&self) -> Option<Rectangle> {
+        if self.min[0] > self.max[0] {
+            return None;
+        }
+        Some([
+            self.min[0],
+            self.min[1],
+            self.max[0] - self.min[0],
+            self.max[1] - self.min[1],
+        ])
+    }
+
+    /// Removes all measured vertices.
+    pub fn reset(&mut self) {
+        self.min = [Scalar::INFINITY; 2];
+        self.max = [Scalar::NEG_INFINITY; 2];
+    }
+
+    /// Adds vertices to the bounds.
+    pub fn add_vertices(&mut self, draw_state: &DrawState, vertices: &[[f32; 2]]) {
+        if let Some(Stencil::Clip(_)) | Some(Stencil::Increment) = draw_state.stencil {
+            return;
+        }
+        for v in vertices {
+            let p = transform_pos(self.inverse, [v[0] as Scalar, v[1] as Scalar]);
+            self.min = [self.min[0].min(p[0]), self.min[1].min(p[1])];
+            self.max = [self.max[0].max(p[0]), self.max[1].max(p[1])];
+        }
+    }
+}
+
+impl<T> Default for Bounds<T> {
+    fn default() -> Self {
+        Bounds::new()
+    }
+}
+
+impl<T: ImageSize> Graphics for Bounds<T> {
+    type Texture = T;
+
+    fn clear_color(&mut self, _color: Color) {}
+
+    fn clear_stencil(&mut self, _value: u8) {}
+
+    fn tri_list<F>(&mut self, draw_state: &DrawState, _color: &[f32; 4], mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+        f(&mut |vertices| self.add_vertices(draw_state, vertices));
+    }
+
+    fn tri_list_c<F>(&mut self, draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        f(&mut |vertices, _| self.add_vertices(draw_state, vertices));
+    }
+
+    fn tri_list_uv<F>(&mut self, draw_state: &DrawState, _color: &[f32; 4], _texture: &T, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+        f(&mut |vertices, _| self.add_vertices(draw_state, vertices));
+    }
+
+    fn tri_list_uv_c<F>(&mut self, draw_state: &DrawState, _texture: &T, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+        f(&mut |vertices, _, _| self.add_vertices(draw_state, vertices));
+    }
+}
+
+/// Accumulates the bounding box of vertices while drawing to a back-end.
+pub struct Forward<'a, G: Graphics> {
+    /// The back-end to draw to.
+    pub g: &'a mut G,
+    /// The measured bounds.
+    pub bounds: Bounds<G::Texture>,
+}
+
+impl<'a, G: Graphics> Forward<'a, G> {
+    /// Creates new empty bounds in the coordinates used by the back-end.
+    pub fn new(g: &'a mut G) -> Forward<'a, G> {
+        Forward {
+            g,
+            bounds: Bounds::new(),
+        }
+    }
+
+    /// Creates new empty bounds in coordinates before transform.
+    pub fn new_transform(g: &'a mut G, transform: Matrix2d) -> Forward<'a, G> {
+        Forward {
+            g,
+            bounds: Bounds::new_transform(transform),
+        }
+    }
+
+    /// Returns the bounding rectangle `[x, y, w, h]`.
+    ///
+    /// Returns `None` if no vertices were drawn.
+    pub fn rect(&self) -> Option<Rectangle> {
+        self.bounds.rect()
+    }
+}
+
+impl<'a, G: Graphics> Graphics for Forward<'a, G> {
+    type Texture = G::Texture;
+
+    fn clear_color(&mut self, color: Color) {
+        self.g.clear_color(color);
+    }
+
+    fn clear_stencil(&mut self, value: u8) {
+        self.g.clear_stencil(value);
+    }
+
+    fn tri_list<F>(&mut self, draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+        let bounds = &mut self.bounds;
+        self.g.tri_list(draw_state, color, |g| {
+            f(&mut |vertices| {
+                bounds.add_vertices(draw_state, vertices);
+                g(vertices)
+            })
+        });
+    }
+
+    fn tri_list_c<F>(&mut self, draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        let bounds = &mut self.bounds;
+        self.g.tri_list_c(draw_state, |g| {
+            f(&mut |vertices, colors| {
+                bounds.add_vertices(draw_state, vertices);
+                g(vertices, colors)
+            })
+        });
+    }
+
+    fn tri_list_uv<F>(
+        &mut self,
+        draw_state: &DrawState,
+        color: &[f32; 4],
+        texture: &G::Texture,
+        mut f: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+        let bounds = &mut self.bounds;
+        self.g.tri_list_uv(draw_state, color, texture, |g| {
+            f(&mut |vertices, texture_coords| {
+                bounds.add_vertices(draw_state, vertices);
+                g(vertices, texture_coords)
+            })
+        });
+    }
+
+    fn tri_list_uv_c<F>(&mut self, draw_state: &DrawState, texture: &G::Texture, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+        let bounds = &mut self.bounds;
+        self.g.tri_list_uv_c(draw_state, texture, |g| {
+            f(&mut |vertices, texture_coords, colors| {
+                bounds.add_vertices(draw_state, vertices);
+                g(vertices, texture_coords, colors)
+            })
+        });
+    }
+
+    fn tri_list_indexed<I, F>(&mut self, draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        I: VertexIndex,
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[I])),
+    {
+        let bounds = &mut self.bounds;
+        self.g.tri_list_indexed(draw_state, color, |g| {
+            f(&mut |vertices, indices| {
+                bounds.add_vertices(draw_state, vertices);
+                g(vertices, indices)
+            })
+        });
+    }
+
+    fn tri_list_c_indexed<I, F>(&mut self, draw_state: &DrawState, mut f: F)
+    where
+        I: VertexIndex,
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]], &[I])),
+    {
+        let bounds = &mut self.bounds;
+        self.g.tri_list_c_indexed(draw_state, |g| {
+            f(&mut |vertices, colors, indices| {
+                bounds.add_vertices(draw_state, vertices);
+                g(vertices, colors, indices)
+            })
+        });
+    }
+
+    fn tri_list_uv_indexed<I, F>(
+        &mut self,
+        draw_state: &DrawState,
+        color: &[f32; 4],
+        texture: &G::Texture,
+        mut f: F,
+    ) where
+        I: VertexIndex,
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[I])),
+    {
+        let bounds = &mut self.bounds;
+        self.g.tri_list_uv_indexed(draw_state, color, texture, |g| {
+            f(&mut |vertices, texture_coords, indices| {
+                bounds.add_vertices(draw_state, vertices);
+                g(vertices, texture_coords, indices)
+            })
+        });
+    }
+
+    fn tri_list_uv_c_indexed<I, F>(
+        &mut self,
+        draw_state: &DrawState,
+        texture: &G::Texture,
+        mut f: F,
+    ) where
+        I: VertexIndex,
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]], &[I])),
+    {
+        let bounds = &mut self.bounds;
+        self.g.tri_list_uv_c_indexed(draw_state, texture, |g| {
+            f(&mut |vertices, texture_coords, colors, indices| {
+                bounds.add_vertices(draw_state, vertices);
+                g(vertices, texture_coords, colors, indices)
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{software::Canvas, software::Texture, Context, Line, Rectangle, Transformed};
+
+    #[test]
+    fn test_bounds() {
+        let c = Context::new_abs(100.0, 100.0);
+        let mut bounds: Bounds<Texture> = Bounds::new();
+        assert_eq!(bounds.rect(), None);
+        Rectangle::new([1.0; 4]).draw(
+            [0.0, 0.0, 50.0, 50.0],
+            &c.draw_state,
+            c.transform,
+            &mut bounds,
+        );
+        assert_eq!(bounds.rect(), Some([-1.0, 0.0, 1.0, 1.0]));
+
+        // Rotated rectangle measured before transform.
+        let transform = c.transform.trans(50.0, 50.0).rot_deg(30.0);
+        let mut bounds: Bounds<Texture> = Bounds::new_transform(transform);
+        Rectangle::new([1.0; 4]).draw(
+            [-5.0, -5.0, 10.0, 10.0],
+            &c.draw_state,
+            transform,
+            &mut bounds,
+        );
+        let rect = bounds.rect().unwrap();
+        for (a, b) in rect.iter().zip(&[-5.0, -5.0, 10.0, 10.0]) {
+            assert!((a - b).abs() < 0.001);
+        }
+
+        // Stencil only shapes are not included.
+        let mut bounds: Bounds<Texture> = Bounds::new();
+        Rectangle::new([1.0; 4]).draw(
+            [0.0, 0.0, 50.0, 50.0],
+            &DrawState::new_clip(),
+            c.transform,
+            &mut bounds,
+        );
+        assert_eq!(bounds.rect(), None);
+    }
+
+    #[test]
+    fn test_forward() {
+        let c = Context::new_abs(10.0, 10.0);
+        let mut canvas = Canvas::new(10, 10);
+        let mut g = Forward::new_transform(&mut canvas, c.transform);
+        Line::new([1.0; 4], 1.0).draw([2.0, 5.0, 8.0, 5.0], &c.draw_state, c.transform, &mut g);
+        let rect = g.rect().unwrap();
+        for (a, b) in rect.iter().zip(&[2.0, 4.0, 6.0, 2.0]) {
+            assert!((a - b).abs() < 0.001);
+        }
+        assert_eq!(canvas.pixel(5, 5), [255; 4]);
+    }
+}
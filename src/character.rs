@@ -2,10 +2,32 @@
 
 use crate::{
     math,
-    types::{FontSize, Scalar},
+    types::{FontSize, Rectangle, Scalar},
     ImageSize,
 };
 
+/// Vertical metrics of a font at some size.
+///
+/// Distances are relative to the baseline, with positive values upwards.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VMetrics {
+    /// The distance from the baseline to the top of the highest glyphs.
+    pub ascent: Scalar,
+    /// The distance from the baseline to the bottom of the lowest glyphs.
+    ///
+    /// This is usually negative.
+    pub descent: Scalar,
+    /// The recommended extra space between lines.
+    pub line_gap: Scalar,
+}
+
+impl VMetrics {
+    /// Returns the recommended distance between baselines.
+    pub fn line_height(&self) -> Scalar {
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
 /// Holds rendered character data.
 #[derive(Clone)]
 pub struct Character<'a, T: ImageSize> {
@@ -62,10 +84,96 @@ pub trait CharacterCache {
     /// Return the width for some given text.
     fn width(&mut self, size: FontSize, text: &str) -> Result<math::Scalar, Self::Error> {
         let mut width = 0.0;
+        let mut prev = None;
         for ch in text.chars() {
+            if let Some(prev) = prev {
+                width += self.kerning(size, prev, ch)?;
+            }
             let character = self.character(size, ch)?;
             width += character.advance_width();
+            prev = Some(ch);
         }
         Ok(width)
     }
+
+    /// Returns the kerning adjustment between a pair of characters.
+    ///
+    /// This is added to the advance width of the first character
+    /// when followed by the second.
+    /// The default implementation returns zero.
+    fn kerning(
+        &mut self,
+        _size: FontSize,
+        _first: char,
+        _second: char,
+    ) -> Result<Scalar, Self::Error> {
+        Ok(0.0)
+    }
+
+    /// Returns the vertical metrics of the font.
+    ///
+    /// The default implementation uses the font size as ascent,
+    /// without descent or line gap.
+    fn v_metrics(&mut self, size: FontSize) -> Result<VMetrics, Self::Error> {
+        Ok(VMetrics {
+            ascent: size as Scalar,
+            descent: 0.0,
+            line_gap: 0.0,
+        })
+    }
+
+    /// Returns the bounding rectangle of some given text.
+    ///
+    /// The rectangle is relative to the start of the baseline,
+    /// in the same coordinates as `Text::draw`.
+    /// It covers the ascent and descent along the advance width,
+    /// and glyphs extending past it.
+    fn measure(&mut self, size: FontSize, text: &str) -> Result<Rectangle, Self::Error> {
+        let v_metrics = self.v_metrics(size)?;
+        let (mut x, mut y) = (0.0, 0.0);
+        let mut min = [0.0, -v_metrics.ascent];
+        let mut max = [0.0, -v_metrics.descent];
+        let mut prev = None;
+        for ch in text.chars() {
+            if let Some(prev) = prev {
+                x += self.kerning(size, prev, ch)?;
+            }
+            prev = Some(ch);
+            let character = self.character(size, ch)?;
+            let (w, h) = (character.atlas_size[0], character.atlas_size[1]);
+            if w > 0.0 && h > 0.0 {
+                let (left, top) = (x + character.left(), y - character.top());
+                min = [min[0].min(left), min[1].min(top)];
+                max = [max[0].max(left + w), max[1].max(top + h)];
+            }
+            x += character.advance_width();
+            y += character.advance_height();
+        }
+        max[0] = max[0].max(x);
+        Ok([min[0], min[1], max[0] - min[0], max[1] - min[1]])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::text_layout::test::MonoCache;
+
+    #[test]
+    fn test_measure() {
+        let mut cache = MonoCache::new();
+        assert_eq!(cache.measure(10, "ab"), Ok([0.0, -10.0, 20.0, 10.0]));
+        assert_eq!(cache.measure(10, " "), Ok([0.0, -10.0, 10.0, 10.0]));
+        // 'g' hangs 2 units left and 4 units below the baseline.
+        assert_eq!(cache.measure(10, "ga"), Ok([-2.0, -10.0, 22.0, 14.0]));
+        assert_eq!(cache.measure(10, ""), Ok([0.0, -10.0, 0.0, 10.0]));
+    }
+
+    #[test]
+    fn test_kerning() {
+        let mut cache = MonoCache::new();
+        // "AV" is kerned by -3 units.
+        assert_eq!(cache.width(10, "AVA"), Ok(24.0));
+        assert_eq!(cache.measure(10, "VA"), Ok([0.0, -10.0, 17.0, 10.0]));
+    }
 }
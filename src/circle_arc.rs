@@ -1,7 +1,8 @@
 //! Draw an arc
 
 use crate::{
-    math::Matrix2d,
+    gradient::Gradient,
+    math::{identity, Matrix2d},
     triangulation,
     types::{Color, Radius, Rectangle, Resolution, Scalar},
     DrawState, Graphics,
@@ -82,6 +83,33 @@ impl CircleArc {
         g.circle_arc(self, rectangle, draw_state, transform);
     }
 
+    /// Draws circle arc filled with a gradient.
+    ///
+    /// The color of the arc is ignored.
+    pub fn draw_gradient<R: Into<Rectangle>, G>(
+        &self,
+        gradient: &Gradient,
+        rectangle: R,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let rectangle = rectangle.into();
+        gradient.draw_tri_list(draw_state, transform, g, |f| {
+            triangulation::with_arc_tri_list(
+                self.start,
+                self.end,
+                self.resolution,
+                identity(),
+                rectangle,
+                self.radius,
+                |vertices| f(vertices),
+            )
+        });
+    }
+
     /// Draws circle arc using triangulation.
     pub fn draw_tri<R: Into<Rectangle>, G>(
         &self,
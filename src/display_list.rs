@@ -0,0 +1,285 @@
+//! Recording draw calls for later replay.
+//!
+//! A `DisplayList` implements `Graphics` by storing every call.
+//! The recorded list can be replayed on any back-end with the same texture type,
+//! which is useful for building a frame on another thread,
+//! or for caching static layers between frames instead of triangulating
+//! the shapes every time.
+//!
+//! Textures are cloned when recorded.
+//! For back-ends where textures are expensive to clone,
+//! use a texture type that shares the texture data, e.g. a handle.
+
+use crate::{types::Color, DrawState, Graphics, ImageSize, BACK_END_MAX_VERTEX_COUNT};
+
+/// A recorded draw call.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command<T> {
+    /// Clears background with a color.
+    ClearColor(Color),
+    /// Clears stencil buffer with a value.
+    ClearStencil(u8),
+    /// Triangles using a solid color.
+    TriList {
+        /// The draw state.
+        draw_state: DrawState,
+        /// The color of all vertices.
+        color: Color,
+        /// The vertex positions.
+        vertices: Vec<[f32; 2]>,
+    },
+    /// Triangles with individual vertex colors.
+    TriListC {
+        /// The draw state.
+        draw_state: DrawState,
+        /// The vertex positions.
+        vertices: Vec<[f32; 2]>,
+        /// The vertex colors.
+        colors: Vec<[f32; 4]>,
+    },
+    /// Triangles using a color and a texture.
+    TriListUv {
+        /// The draw state.
+        draw_state: DrawState,
+        /// The color of all vertices.
+        color: Color,
+        /// The texture.
+        texture: T,
+        /// The vertex positions.
+        vertices: Vec<[f32; 2]>,
+        /// The texture coordinates.
+        texture_coords: Vec<[f32; 2]>,
+    },
+    /// Triangles using a texture and individual vertex colors.
+    TriListUvC {
+        /// The draw state.
+        draw_state: DrawState,
+        /// The texture.
+        texture: T,
+        /// The vertex positions.
+        vertices: Vec<[f32; 2]>,
+        /// The texture coordinates.
+        texture_coords: Vec<[f32; 2]>,
+        /// The vertex colors.
+        colors: Vec<[f32; 4]>,
+    },
+}
+
+/// Stores draw calls that can be replayed on another back-end.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayList<T> {
+    /// The recorded draw calls, in order.
+    pub commands: Vec<Command<T>>,
+}
+
+impl<T> DisplayList<T> {
+    /// Creates a new empty display list.
+    pub fn new() -> DisplayList<T> {
+        DisplayList { commands: vec![] }
+    }
+
+    /// Removes all recorded draw calls.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Returns `true` if there are no recorded draw calls.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Replays the recorded draw calls on a back-end.
+    ///
+    /// Vertices are sent in chunks of at most `BACK_END_MAX_VERTEX_COUNT`.
+    pub fn draw<G>(&self, g: &mut G)
+    where
+        G: Graphics<Texture = T>,
+    {
+        let n = BACK_END_MAX_VERTEX_COUNT;
+        for command in &self.commands {
+            match command {
+                Command::ClearColor(color) => g.clear_color(*color),
+                Command::ClearStencil(value) => g.clear_stencil(*value),
+                Command::TriList {
+                    draw_state,
+                    color,
+                    vertices,
+                } => g.tri_list(draw_state, color, |f| {
+                    for v in vertices.chunks(n) {
+                        f(v)
+                    }
+                }),
+                Command::TriListC {
+                    draw_state,
+                    vertices,
+                    colors,
+                } => g.tri_list_c(draw_state, |f| {
+                    for (v, c) in vertices.chunks(n).zip(colors.chunks(n)) {
+                        f(v, c)
+                    }
+                }),
+                Command::TriListUv {
+                    draw_state,
+                    color,
+                    texture,
+                    vertices,
+                    texture_coords,
+                } => g.tri_list_uv(draw_state, color, texture, |f| {
+                    for (v, t) in vertices.chunks(n).zip(texture_coords.chunks(n)) {
+                        f(v, t)
+                    }
+                }),
+                Command::TriListUvC {
+                    draw_state,
+                    texture,
+                    vertices,
+                    texture_coords,
+                    colors,
+                } => g.tri_list_uv_c(draw_state, texture, |f| {
+                    for ((v, t), c) in vertices
+                        .chunks(n)
+                        .zip(texture_coords.chunks(n))
+                        .zip(colors.chunks(n))
+                    {
+                        f(v, t, c)
+                    }
+                }),
+            }
+        }
+    }
+}
+
+impl<T> Default for DisplayList<T> {
+    fn default() -> Self {
+        DisplayList::new()
+    }
+}
+
+impl<T> Graphics for DisplayList<T>
+where
+    T: ImageSize + Clone,
+{
+    type Texture = T;
+
+    fn clear_color(&mut self, color: Color) {
+        self.commands.push(Command::ClearColor(color));
+    }
+
+    fn clear_stencil(&mut self, value: u8) {
+        self.commands.push(Command::ClearStencil(value));
+    }
+
+    fn tri_list<F>(&mut self, draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+        let mut vertices = vec![];
+        f(&mut |v| vertices.extend_from_slice(v));
+        self.commands.push(Command::TriList {
+            draw_state: *draw_state,
+            color: *color,
+            vertices,
+        });
+    }
+
+    fn tri_list_c<F>(&mut self, draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        let mut vertices = vec![];
+        let mut colors = vec![];
+        f(&mut |v, c| {
+            vertices.extend_from_slice(v);
+            colors.extend_from_slice(c);
+        });
+        self.commands.push(Command::TriListC {
+            draw_state: *draw_state,
+            vertices,
+            colors,
+        });
+    }
+
+    fn tri_list_uv<F>(&mut self, draw_state: &DrawState, color: &[f32; 4], texture: &T, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+        let mut vertices = vec![];
+        let mut texture_coords = vec![];
+        f(&mut |v, t| {
+            vertices.extend_from_slice(v);
+            texture_coords.extend_from_slice(t);
+        });
+        self.commands.push(Command::TriListUv {
+            draw_state: *draw_state,
+            color: *color,
+            texture: texture.clone(),
+            vertices,
+            texture_coords,
+        });
+    }
+
+    fn tri_list_uv_c<F>(&mut self, draw_state: &DrawState, texture: &T, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+        let mut vertices = vec![];
+        let mut texture_coords = vec![];
+        let mut colors = vec![];
+        f(&mut |v, t, c| {
+            vertices.extend_from_slice(v);
+            texture_coords.extend_from_slice(t);
+            colors.extend_from_slice(c);
+        });
+        self.commands.push(Command::TriListUvC {
+            draw_state: *draw_state,
+            texture: texture.clone(),
+            vertices,
+            texture_coords,
+            colors,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{software::Canvas, Context, Ellipse, Rectangle};
+
+    #[test]
+    fn test_replay() {
+        let c = Context::new_abs(16.0, 16.0);
+        let mut list = DisplayList::new();
+        crate::clear([0.0, 0.0, 0.0, 1.0], &mut list);
+        Rectangle::new([1.0, 0.0, 0.0, 1.0]).draw(
+            [2.0, 2.0, 8.0, 8.0],
+            &c.draw_state,
+            c.transform,
+            &mut list,
+        );
+        Ellipse::new([0.0, 1.0, 0.0, 0.5]).draw(
+            [4.0, 4.0, 10.0, 10.0],
+            &c.draw_state,
+            c.transform,
+            &mut list,
+        );
+        assert_eq!(list.commands.len(), 4);
+
+        let mut a = Canvas::new(16, 16);
+        list.draw(&mut a);
+        let mut b = Canvas::new(16, 16);
+        crate::clear([0.0, 0.0, 0.0, 1.0], &mut b);
+        Rectangle::new([1.0, 0.0, 0.0, 1.0]).draw(
+            [2.0, 2.0, 8.0, 8.0],
+            &c.draw_state,
+            c.transform,
+            &mut b,
+        );
+        Ellipse::new([0.0, 1.0, 0.0, 0.5]).draw(
+            [4.0, 4.0, 10.0, 10.0],
+            &c.draw_state,
+            c.transform,
+            &mut b,
+        );
+        assert_eq!(a.pixels(), b.pixels());
+    }
+}
@@ -2,9 +2,12 @@
 
 pub use crate::rectangle::{centered, centered_square as circle};
 use crate::{
-    math::Matrix2d,
+    gradient::Gradient,
+    image::Image,
+    line::{self, Dash, Join},
+    math::{identity, Matrix2d},
     triangulation,
-    types::{Color, Radius, Rectangle, Resolution},
+    types::{Color, Radius, Rectangle, Resolution, SourceRectangle},
     DrawState, Graphics,
 };
 
@@ -15,6 +18,11 @@ pub struct Border {
     pub color: Color,
     /// The border radius
     pub radius: Radius,
+    /// The dash pattern of the border
+    ///
+    /// Dashes have `line::Shape::Square` ends.
+    /// The segments approximating the ellipse are joined with `Join::Miter(4.0)`.
+    pub dash: Option<Dash>,
 }
 
 /// An ellipse with filled color
@@ -42,7 +50,11 @@ impl Ellipse {
     pub fn new_border(color: Color, radius: Radius) -> Ellipse {
         Ellipse {
             color: [0.0; 4],
-            border: Some(Border { color, radius }),
+            border: Some(Border {
+                color,
+                radius,
+                dash: None,
+            }),
             resolution: 128,
         }
     }
@@ -109,6 +121,64 @@ impl Ellipse {
         g.ellipse(self, rectangle, draw_state, transform);
     }
 
+    /// Draws ellipse filled with a gradient.
+    ///
+    /// The color of the ellipse is ignored.
+    /// The border is drawn with its own color.
+    pub fn draw_gradient<R: Into<Rectangle>, G>(
+        &self,
+        gradient: &Gradient,
+        rectangle: R,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let rectangle = rectangle.into();
+        gradient.draw_tri_list(draw_state, transform, g, |f| {
+            triangulation::with_ellipse_tri_list(
+                self.resolution,
+                identity(),
+                rectangle,
+                |vertices| f(vertices),
+            )
+        });
+        self.draw_border(rectangle, draw_state, transform, g);
+    }
+
+    /// Draws ellipse filled with a texture.
+    ///
+    /// The source rectangle, or the whole texture when `None`,
+    /// is stretched over the rectangle of the ellipse.
+    /// The texture is multiplied with the color of the ellipse.
+    /// The border is drawn with its own color.
+    pub fn draw_texture<R: Into<Rectangle>, G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        source_rectangle: Option<SourceRectangle>,
+        rectangle: R,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let rectangle = rectangle.into();
+        Image::new_color(self.color)
+            .maybe_src_rect(source_rectangle)
+            .rect(rectangle)
+            .draw_tri_list(texture, draw_state, transform, g, |f| {
+                triangulation::with_ellipse_tri_list(
+                    self.resolution,
+                    identity(),
+                    rectangle,
+                    |vertices| f(vertices),
+                )
+            });
+        self.draw_border(rectangle, draw_state, transform, g);
+    }
+
     /// Draws ellipse using triangulation.
     pub fn draw_tri<R: Into<Rectangle>, G>(
         &self,
@@ -120,28 +190,55 @@ impl Ellipse {
         G: Graphics,
     {
         let rectangle = rectangle.into();
-        g.tri_list(draw_state, &self.color, |f| {
-            triangulation::with_ellipse_tri_list(
+        g.tri_list_indexed(draw_state, &self.color, |f| {
+            triangulation::with_ellipse_tri_list_indexed(
                 self.resolution,
                 transform,
                 rectangle,
-                |vertices| f(vertices),
+                |vertices, indices| f(vertices, indices),
             )
         });
+        self.draw_border(rectangle, draw_state, transform, g);
+    }
 
+    /// Draws the border of ellipse, if any.
+    fn draw_border<G>(
+        &self,
+        rectangle: Rectangle,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
         if let Some(Border {
             color,
             radius: border_radius,
+            dash,
         }) = self.border
         {
-            g.tri_list(draw_state, &color, |f| {
-                triangulation::with_ellipse_border_tri_list(
+            g.tri_list(draw_state, &color, |f| match dash {
+                None => triangulation::with_ellipse_border_tri_list(
                     self.resolution,
                     transform,
                     rectangle,
                     border_radius,
                     |vertices| f(vertices),
-                )
+                ),
+                Some(dash) => {
+                    let points: Vec<_> =
+                        triangulation::ellipse_points(self.resolution, rectangle).collect();
+                    triangulation::with_dashed_polyline_tri_list(
+                        transform,
+                        &points,
+                        true,
+                        border_radius,
+                        dash,
+                        Join::Miter(4.0),
+                        line::Shape::Square,
+                        |vertices| f(vertices),
+                    )
+                }
             });
         }
     }
@@ -156,6 +253,105 @@ mod test {
         let _ellipse = Ellipse::new([1.0; 4]).color([0.0; 4]).border(Border {
             color: [1.0; 4],
             radius: 3.0,
+            dash: None,
         });
     }
+
+    #[test]
+    fn test_ellipse_indexed() {
+        use crate::{display_list::DisplayList, software::Texture, Context};
+
+        let c = Context::new_abs(100.0, 100.0);
+        let rect = [10.0, 10.0, 50.0, 30.0];
+        // High resolution requires several chunks.
+        for &resolution in &[3, 128, 2000] {
+            let mut indexed: DisplayList<Texture> = DisplayList::new();
+            Ellipse::new([1.0; 4]).resolution(resolution).draw(
+                rect,
+                &c.draw_state,
+                c.transform,
+                &mut indexed,
+            );
+            let mut expected: DisplayList<Texture> = DisplayList::new();
+            expected.tri_list(&c.draw_state, &[1.0; 4], |f| {
+                triangulation::with_ellipse_tri_list(resolution, c.transform, rect, |vertices| {
+                    f(vertices)
+                })
+            });
+            assert_eq!(indexed, expected);
+        }
+    }
+
+    #[test]
+    fn test_draw_border() {
+        use crate::{
+            display_list::{Command, DisplayList},
+            software::Texture,
+            Context,
+        };
+
+        let c = Context::new_abs(100.0, 100.0);
+        let rect = [10.0, 10.0, 50.0, 30.0];
+        let ellipse = Ellipse::new([0.0; 4]).border(Border {
+            color: [1.0; 4],
+            radius: 1.0,
+            dash: None,
+        });
+        // Transparent ellipses are still drawn, e.g. for writing to the stencil buffer.
+        let mut list: DisplayList<Texture> = DisplayList::new();
+        ellipse.draw(rect, &c.draw_state, c.transform, &mut list);
+        assert_eq!(list.commands.len(), 2);
+
+        // Only the gradient and the border are drawn.
+        let mut list: DisplayList<Texture> = DisplayList::new();
+        let gradient = Gradient::linear([0.0; 2], [1.0; 2])
+            .stop(0.0, [1.0; 4])
+            .stop(1.0, [0.0; 4]);
+        ellipse.draw_gradient(&gradient, rect, &c.draw_state, c.transform, &mut list);
+        assert_eq!(list.commands.len(), 2);
+        match &list.commands[1] {
+            Command::TriList { color, .. } => assert_eq!(*color, [1.0; 4]),
+            _ => panic!("Expected tri list"),
+        }
+    }
+
+    #[test]
+    fn test_draw_texture() {
+        use crate::{
+            software::{Canvas, Texture},
+            Context,
+        };
+
+        // White left half and black right half.
+        let mut pixels = vec![];
+        for p in &[[255; 4], [0, 0, 0, 255], [255; 4], [0, 0, 0, 255]] {
+            pixels.extend_from_slice(p);
+        }
+        let texture = Texture::from_rgba8(2, 2, pixels).unwrap();
+        let c = Context::new_abs(20.0, 20.0);
+        let mut canvas = Canvas::new(20, 20);
+        Ellipse::new([1.0, 1.0, 1.0, 1.0]).draw_texture(
+            &texture,
+            None,
+            [0.0, 0.0, 20.0, 20.0],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(1, 1), [0; 4]);
+        assert_eq!(canvas.pixel(5, 10), [255; 4]);
+        assert_eq!(canvas.pixel(15, 10), [0, 0, 0, 255]);
+
+        // Only the right half of the texture.
+        let mut canvas = Canvas::new(20, 20);
+        Ellipse::new([1.0, 0.0, 0.0, 1.0]).draw_texture(
+            &texture,
+            Some([1.0, 0.0, 1.0, 2.0]),
+            [0.0, 0.0, 20.0, 20.0],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(5, 10), [0, 0, 0, 255]);
+    }
 }
@@ -1,4 +1,7 @@
 //! Glyph caching using the RustType library.
+//!
+//! Characters missing from the font are looked up in fallback fonts,
+//! in the order they were passed to `GlyphCache::from_fonts`.
 
 use std::{collections::HashMap, fs::File, hash::BuildHasherDefault, io::Read, path::Path};
 
@@ -8,7 +11,7 @@ use texture::{ops, CreateTexture, Format, TextureSettings, UpdateTexture};
 
 use self::fnv::FnvHasher;
 use crate::{
-    character::{Character, CharacterCache},
+    character::{Character, CharacterCache, VMetrics},
     texture_packer::TexturePacker,
     types::{FontSize, Scalar},
     ImageSize,
@@ -40,6 +43,10 @@ pub const ATLAS_SIZE: [u32; 2] = [256; 2];
 pub struct GlyphCache<'a, F, T> {
     /// The font.
     pub font: rusttype::Font<'a>,
+    /// The fonts used in order for characters missing from `font`.
+    ///
+    /// These are fixed on construction, because cached characters are not reloaded.
+    fallbacks: Vec<rusttype::Font<'a>>,
     /// The factory used to create textures.
     pub factory: F,
     /// The settings to render the font with.
@@ -55,9 +62,22 @@ where
 {
     /// Constructs a GlyphCache from a Font.
     pub fn from_font(font: rusttype::Font<'a>, factory: F, settings: TextureSettings) -> Self {
+        Self::from_fonts(font, Vec::new(), factory, settings)
+    }
+
+    /// Constructs a GlyphCache from a font and fallback fonts.
+    ///
+    /// Characters missing from `font` are looked up in `fallbacks` in order.
+    pub fn from_fonts(
+        font: rusttype::Font<'a>,
+        fallbacks: Vec<rusttype::Font<'a>>,
+        factory: F,
+        settings: TextureSettings,
+    ) -> Self {
         let fnv = BuildHasherDefault::<FnvHasher>::default();
         GlyphCache {
             font,
+            fallbacks,
             factory,
             settings,
             texture_packer: TexturePacker::new(),
@@ -85,6 +105,7 @@ where
         ))?;
         Ok(GlyphCache {
             font,
+            fallbacks: Vec::new(),
             factory,
             settings,
             texture_packer: TexturePacker::new(),
@@ -102,6 +123,11 @@ where
         Ok(Self::from_font(font, factory, settings))
     }
 
+    /// Returns the fallback fonts.
+    pub fn fallbacks(&self) -> &[rusttype::Font<'a>] {
+        &self.fallbacks
+    }
+
     /// Load all characters in the `chars` iterator for `size`
     pub fn preload_chars<I>(&mut self, size: FontSize, chars: I) -> Result<(), T::Error>
     where
@@ -179,15 +205,15 @@ where
                 })
             }
             Entry::Vacant(v) => {
-                // this is only None for invalid GlyphIds,
-                // but char is converted to a Codepoint which must result in a glyph.
-                let glyph = self.font.glyph(ch);
                 let scale = rt::Scale::uniform(size as f32);
-                let mut glyph = glyph.scaled(scale);
+                let font = find_font(&self.font, &self.fallbacks, ch).map_or(&self.font, |f| f.1);
+                let mut glyph = font.glyph(ch).scaled(scale);
 
                 // some fonts do not contain glyph zero as fallback, instead try U+FFFD.
                 if glyph.id() == rt::GlyphId(0) && !glyph.build_outline(&mut EmptyOutlineBuilder) {
-                    glyph = self.font.glyph('\u{FFFD}').scaled(scale);
+                    let font = find_font(&self.font, &self.fallbacks, '\u{FFFD}')
+                        .map_or(&self.font, |f| f.1);
+                    glyph = font.glyph('\u{FFFD}').scaled(scale);
                 }
 
                 let h_metrics = glyph.h_metrics();
@@ -292,6 +318,50 @@ where
             }
         }
     }
+
+    fn kerning(
+        &mut self,
+        size: FontSize,
+        first: char,
+        second: char,
+    ) -> Result<Scalar, Self::Error> {
+        // Kerning only applies between characters of the same font.
+        let font = match (
+            find_font(&self.font, &self.fallbacks, first),
+            find_font(&self.font, &self.fallbacks, second),
+        ) {
+            (Some((a, font)), Some((b, _))) if a == b => font,
+            _ => return Ok(0.0),
+        };
+        let size = ((size as f32) * 1.333).round(); // convert points to pixels
+        let scale = rusttype::Scale::uniform(size);
+        Ok(font.pair_kerning(scale, first, second) as Scalar)
+    }
+
+    fn v_metrics(&mut self, size: FontSize) -> Result<VMetrics, Self::Error> {
+        // The metrics of the primary font are used.
+        let size = ((size as f32) * 1.333).round(); // convert points to pixels
+        let v_metrics = self.font.v_metrics(rusttype::Scale::uniform(size));
+        Ok(VMetrics {
+            ascent: v_metrics.ascent as Scalar,
+            descent: v_metrics.descent as Scalar,
+            line_gap: v_metrics.line_gap as Scalar,
+        })
+    }
+}
+
+/// Finds the first font containing a character.
+///
+/// Returns the index of the font, where 0 is `font` and 1 is the first fallback.
+fn find_font<'b, 'a>(
+    font: &'b rusttype::Font<'a>,
+    fallbacks: &'b [rusttype::Font<'a>],
+    ch: char,
+) -> Option<(usize, &'b rusttype::Font<'a>)> {
+    std::iter::once(font)
+        .chain(fallbacks)
+        .enumerate()
+        .find(|(_, font)| font.glyph(ch).id() != rusttype::GlyphId(0))
 }
 
 fn empty<F, T: CreateTexture<F>>(
@@ -321,3 +391,137 @@ fn update_memory_alpha<F, T: UpdateTexture<F>>(
     let buffer: Vec<u8> = ops::alpha_to_rgba8(buf, size);
     texture.update(factory, Format::Rgba8, &buffer, offset, size)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::software::Texture;
+
+    /// Builds a TrueType font without outlines.
+    ///
+    /// Each character gets a glyph with an advance width in font units,
+    /// where the font is 1000 units high.
+    /// Kerning pairs are in font units.
+    #[allow(clippy::manual_div_ceil)] // `usize::div_ceil` requires Rust 1.73.
+    fn font(chars: &[(char, u16)], kerning: &[(char, char, i16)]) -> rusttype::Font<'static> {
+        fn table(data: &mut Vec<u8>, values: &[u32], sizes: &[usize]) {
+            for (&v, &n) in values.iter().zip(sizes) {
+                data.extend_from_slice(&v.to_be_bytes()[4 - n..]);
+            }
+        }
+        let glyphs = chars.len() as u32 + 1;
+        let glyph = |ch: char| chars.iter().position(|c| c.0 == ch).unwrap() as u32 + 1;
+
+        let mut head = vec![];
+        table(
+            &mut head,
+            &[0x10000, 0x10000, 0, 0x5F0F3CF5, 0, 1000],
+            &[4, 4, 4, 4, 2, 2],
+        );
+        head.resize(54, 0);
+        let mut hhea = vec![];
+        table(&mut hhea, &[0x10000, 800, 0xFF38], &[4, 2, 2]);
+        hhea.resize(34, 0);
+        table(&mut hhea, &[glyphs], &[2]);
+        let mut maxp = vec![];
+        table(&mut maxp, &[0x5000, glyphs], &[4, 2]);
+        let mut hmtx = vec![0; 4];
+        for &(_, advance) in chars {
+            table(&mut hmtx, &[advance as u32, 0], &[2, 2]);
+        }
+        let mut cmap = vec![];
+        let n = chars.len() as u32;
+        table(&mut cmap, &[0, 1, 0, 4, 12], &[2, 2, 2, 2, 4]);
+        table(&mut cmap, &[12, 0, 16 + 12 * n, 0, n], &[2, 2, 4, 4, 4]);
+        for (i, &(ch, _)) in chars.iter().enumerate() {
+            table(&mut cmap, &[ch as u32, ch as u32, i as u32 + 1], &[4, 4, 4]);
+        }
+        let mut pairs: Vec<[u32; 3]> = kerning
+            .iter()
+            .map(|&(a, b, v)| [glyph(a), glyph(b), v as u16 as u32])
+            .collect();
+        pairs.sort();
+        let mut kern = vec![];
+        let n = pairs.len() as u32;
+        table(&mut kern, &[0, 1, 0, 14 + 6 * n, 1], &[2, 2, 2, 2, 2]);
+        table(&mut kern, &[n, 0, 0, 0], &[2, 2, 2, 2]);
+        for pair in &pairs {
+            table(&mut kern, pair, &[2, 2, 2]);
+        }
+
+        let tables = [
+            (b"cmap", cmap),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"kern", kern),
+            (b"maxp", maxp),
+        ];
+        let mut data = vec![];
+        table(
+            &mut data,
+            &[0x10000, tables.len() as u32, 0, 0, 0],
+            &[4, 2, 2, 2, 2],
+        );
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, t) in &tables {
+            data.extend_from_slice(&tag[..]);
+            table(&mut data, &[0, offset as u32, t.len() as u32], &[4, 4, 4]);
+            offset += (t.len() + 3) / 4 * 4;
+        }
+        for (_, t) in &tables {
+            data.extend_from_slice(t);
+            data.resize((data.len() + 3) / 4 * 4, 0);
+        }
+        rusttype::Font::try_from_vec(data).unwrap()
+    }
+
+    #[test]
+    fn test_find_font() {
+        let font_a = font(&[('A', 500)], &[]);
+        let fallbacks = vec![
+            font(&[('A', 500), ('B', 500)], &[]),
+            font(&[('B', 500), ('C', 500)], &[]),
+        ];
+        let index = |ch| find_font(&font_a, &fallbacks, ch).map(|f| f.0);
+        assert_eq!(index('A'), Some(0));
+        assert_eq!(index('B'), Some(1));
+        assert_eq!(index('C'), Some(2));
+        assert_eq!(index('D'), None);
+    }
+
+    #[test]
+    fn test_fallback() {
+        let fallbacks = vec![font(&[('B', 300)], &[])];
+        let mut cache: GlyphCache<'_, (), Texture> = GlyphCache::from_fonts(
+            font(&[('A', 500)], &[]),
+            fallbacks,
+            (),
+            TextureSettings::new(),
+        );
+        // A font size of 75 points is 100 pixels, so 1000 font units are 100 pixels.
+        assert_eq!(cache.character(75, 'A').unwrap().advance_width(), 50.0);
+        let b = cache.character(75, 'B').unwrap();
+        assert_eq!(b.advance_width(), 30.0);
+        assert!(!b.is_invalid);
+        assert!(cache.character(75, 'C').unwrap().is_invalid);
+        let v_metrics = cache.v_metrics(75).unwrap();
+        assert_eq!((v_metrics.ascent, v_metrics.descent), (80.0, -20.0));
+    }
+
+    #[test]
+    fn test_kerning() {
+        let fallbacks = vec![font(
+            &[('A', 500), ('W', 500)],
+            &[('A', 'W', -200), ('W', 'W', -100)],
+        )];
+        let primary = font(&[('A', 500), ('V', 500)], &[('A', 'V', -200)]);
+        let mut cache: GlyphCache<'_, (), Texture> =
+            GlyphCache::from_fonts(primary, fallbacks, (), TextureSettings::new());
+        assert_eq!(cache.kerning(75, 'A', 'V'), Ok(-20.0));
+        assert_eq!(cache.kerning(75, 'W', 'W'), Ok(-10.0));
+        // 'A' is from the primary font and 'W' from the fallback.
+        assert_eq!(cache.kerning(75, 'A', 'W'), Ok(0.0));
+        assert_eq!(cache.width(75, "AVA"), Ok(130.0));
+    }
+}
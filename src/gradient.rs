@@ -0,0 +1,631 @@
+//! Gradient paints.
+//!
+//! A `Gradient` assigns colors to positions by interpolating between color stops.
+//! Shapes are drawn with a gradient using e.g. `Rectangle::draw_gradient`.
+//! The positions are in the same coordinates as the shape, before transform.
+//!
+//! The triangles of the shape are split where the color changes
+//! between stops, and the colors are computed per vertex.
+//! For radial and conic gradients, the triangles are subdivided
+//! until the colors are close to the exact color ramp,
+//! or until they are about a pixel in size.
+//! The number of triangles grows with the curvature of the color ramp
+//! and with the length of stop boundaries on screen,
+//! but not with the size of the shape before transform.
+//! The result is sent to the back-end using `Graphics::tri_list_c`.
+//!
+//! ```
+//! use graphics::{gradient::Gradient, software::Canvas, Context, Rectangle};
+//!
+//! let c = Context::new_abs(100.0, 20.0);
+//! let gradient = Gradient::linear([0.0, 0.0], [100.0, 0.0])
+//!     .stop(0.0, [1.0, 0.0, 0.0, 1.0])
+//!     .stop(1.0, [0.0, 0.0, 1.0, 1.0]);
+//! let mut canvas = Canvas::new(100, 20);
+//! Rectangle::new([1.0; 4]).draw_gradient(
+//!     &gradient,
+//!     [0.0, 0.0, 100.0, 20.0],
+//!     &c.draw_state,
+//!     c.transform,
+//!     &mut canvas,
+//! );
+//! ```
+
+use crate::{
+    math::{add, dot, mul_scalar, square_len, sub, Matrix2d, Scalar, Vec2d},
+    radians::Radians,
+    triangulation::{tx, ty},
+    types::Color,
+    DrawState, Graphics, BACK_END_MAX_VERTEX_COUNT as BUFFER_SIZE,
+};
+
+/// The largest error in color channels when approximating
+/// radial and conic gradients with triangles.
+const COLOR_TOLERANCE: f32 = 1.0 / 256.0;
+
+/// The size of triangles that are not subdivided further,
+/// in normalized device coordinates.
+///
+/// This is about a pixel on a viewport 2048 pixels wide.
+const MIN_EXTENT: f32 = 1.0 / 1024.0;
+
+/// The maximum number of times a triangle is subdivided.
+///
+/// Triangles covering the viewport reach `MIN_EXTENT` before this,
+/// so it only limits the cost of extreme transforms.
+const MAX_DEPTH: u32 = 12;
+
+/// The largest distance from the center to the focal point,
+/// relative to the radius.
+const MAX_FOCAL: Scalar = 0.99;
+
+/// A color at an offset of a gradient.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stop {
+    /// The offset, usually between 0 and 1.
+    pub offset: Scalar,
+    /// The color.
+    pub color: Color,
+}
+
+/// The geometry of a gradient.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Kind {
+    /// Changes color along the line from start to end.
+    ///
+    /// The offset is 0 at start and 1 at end.
+    /// Lines perpendicular to the gradient have the same color.
+    Linear {
+        /// The start point.
+        start: Vec2d,
+        /// The end point.
+        end: Vec2d,
+    },
+    /// Changes color along circles.
+    ///
+    /// The offset is 0 at the focal point and 1 on the circle.
+    /// The focal point is the center unless set with `Gradient::focal`.
+    Radial {
+        /// The center of the circle.
+        center: Vec2d,
+        /// The radius of the circle.
+        radius: Scalar,
+        /// The focal point, inside the circle.
+        focal: Vec2d,
+    },
+    /// Changes color with the angle around a center.
+    ///
+    /// The offset goes from 0 to 1 clockwise in screen coordinates,
+    /// starting at the start angle.
+    Conic {
+        /// The center.
+        center: Vec2d,
+        /// The start angle in radians.
+        angle: Scalar,
+    },
+}
+
+/// A paint with colors interpolated between stops.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    /// The geometry of the gradient.
+    pub kind: Kind,
+    /// The color stops, sorted by offset.
+    pub stops: Vec<Stop>,
+}
+
+impl Gradient {
+    /// Creates a new linear gradient without color stops.
+    pub fn linear(start: Vec2d, end: Vec2d) -> Gradient {
+        Gradient {
+            kind: Kind::Linear { start, end },
+            stops: vec![],
+        }
+    }
+
+    /// Creates a new radial gradient without color stops.
+    pub fn radial(center: Vec2d, radius: Scalar) -> Gradient {
+        Gradient {
+            kind: Kind::Radial {
+                center,
+                radius,
+                focal: center,
+            },
+            stops: vec![],
+        }
+    }
+
+    /// Creates a new conic gradient without color stops.
+    pub fn conic(center: Vec2d, angle: Scalar) -> Gradient {
+        Gradient {
+            kind: Kind::Conic { center, angle },
+            stops: vec![],
+        }
+    }
+
+    /// Sets the focal point of a radial gradient.
+    ///
+    /// Focal points outside the circle are moved to just inside the circle.
+    /// Does nothing for other kinds of gradients.
+    pub fn focal(mut self, value: Vec2d) -> Self {
+        if let Kind::Radial {
+            center,
+            radius,
+            ref mut focal,
+        } = self.kind
+        {
+            let d = sub(value, center);
+            let len = square_len(d).sqrt();
+            let max = radius * MAX_FOCAL;
+            *focal = if len > max {
+                add(center, mul_scalar(d, max / len))
+            } else {
+                value
+            };
+        }
+        self
+    }
+
+    /// Adds a color stop.
+    ///
+    /// Stops at the same offset change color abruptly,
+    /// in the order they were added.
+    /// Stops with a NaN offset are ignored.
+    pub fn stop(mut self, offset: Scalar, color: Color) -> Self {
+        if offset.is_nan() {
+            return self;
+        }
+        self.stops.push(Stop { offset, color });
+        // Stable sort keeps the order of stops at same offset.
+        self.stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        self
+    }
+
+    /// Returns the offset of the gradient at a position.
+    pub fn offset_at(&self, pos: Vec2d) -> Scalar {
+        match self.kind {
+            Kind::Linear { start, end } => {
+                let d = [end[0] - start[0], end[1] - start[1]];
+                let len2 = d[0] * d[0] + d[1] * d[1];
+                if len2 == 0.0 {
+                    return 0.0;
+                }
+                ((pos[0] - start[0]) * d[0] + (pos[1] - start[1]) * d[1]) / len2
+            }
+            Kind::Radial {
+                center,
+                radius,
+                focal,
+            } => {
+                if radius <= 0.0 {
+                    return 0.0;
+                }
+                // Solve `|d - t * e| = t * radius` for the circle
+                // through `pos` at offset `t`, scaled from the focal point.
+                let d = sub(pos, focal);
+                let e = sub(center, focal);
+                let de = dot(d, e);
+                let a = square_len(e) - radius * radius;
+                let disc = de * de - a * square_len(d);
+                (de - disc.max(0.0).sqrt()) / a
+            }
+            Kind::Conic { center, angle } => {
+                let d = sub(pos, center);
+                let turn = <Scalar as Radians>::_360();
+                (d[1].atan2(d[0]) - angle).rem_euclid(turn) / turn
+            }
+        }
+    }
+
+    /// Returns the color at an offset.
+    ///
+    /// Offsets outside the stops use the color of the nearest stop.
+    /// Returns transparent when there are no stops.
+    pub fn color_at_offset(&self, offset: Scalar) -> Color {
+        self.color_between_stops(offset, offset)
+    }
+
+    /// Returns the color at an offset,
+    /// using the stops around a reference offset.
+    ///
+    /// This gives the color on the correct side of abrupt changes.
+    fn color_between_stops(&self, offset: Scalar, reference: Scalar) -> Color {
+        let stops = &self.stops;
+        let (first, last) = match (stops.first(), stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return [0.0; 4],
+        };
+        if reference <= first.offset {
+            return first.color;
+        }
+        if reference >= last.offset {
+            return last.color;
+        }
+        let i = stops
+            .iter()
+            .rposition(|s| s.offset <= reference)
+            .unwrap_or(0);
+        let (a, b) = (&stops[i], &stops[i + 1]);
+        let t = ((offset - a.offset) / (b.offset - a.offset)).clamp(0.0, 1.0) as f32;
+        let mut color = [0.0; 4];
+        for (k, c) in color.iter_mut().enumerate() {
+            *c = a.color[k] + (b.color[k] - a.color[k]) * t;
+        }
+        color
+    }
+
+    /// Returns the color at a position.
+    pub fn color_at(&self, pos: Vec2d) -> Color {
+        self.color_at_offset(self.offset_at(pos))
+    }
+
+    /// Draws triangles filled with the gradient.
+    ///
+    /// `triangles` streams the triangles in coordinates before transform,
+    /// in the same way as the closures passed to `Graphics::tri_list`.
+    pub fn draw_tri_list<F, G>(
+        &self,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+        mut triangles: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+        G: Graphics,
+    {
+        g.tri_list_c(draw_state, |f| {
+            let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            let mut colors: [[f32; 4]; BUFFER_SIZE] = [[0.0; 4]; BUFFER_SIZE];
+            let mut i = 0;
+            let mut emit = |tri: [Vec2d; 3], tri_colors: [Color; 3]| {
+                for (p, color) in tri.iter().zip(&tri_colors) {
+                    vertices[i] = [tx(transform, p[0], p[1]), ty(transform, p[0], p[1])];
+                    colors[i] = *color;
+                    i += 1;
+                }
+                // The buffer size is a multiple of 3.
+                if i == BUFFER_SIZE {
+                    f(&vertices, &colors);
+                    i = 0;
+                }
+            };
+            triangles(&mut |tris| {
+                for tri in tris.chunks_exact(3) {
+                    let p = |k: usize| [tri[k][0] as Scalar, tri[k][1] as Scalar];
+                    self.split([p(0), p(1), p(2)], transform, &mut emit);
+                }
+            });
+            if i > 0 {
+                f(&vertices[0..i], &colors[0..i]);
+            }
+        });
+    }
+
+    /// Splits a triangle into triangles where the colors
+    /// can be linearly interpolated between the vertices.
+    fn split(
+        &self,
+        tri: [Vec2d; 3],
+        transform: Matrix2d,
+        emit: &mut dyn FnMut([Vec2d; 3], [Color; 3]),
+    ) {
+        if !matches!(self.kind, Kind::Linear { .. }) {
+            self.subdivide(tri, transform, MAX_DEPTH, emit);
+            return;
+        }
+        let offsets = [
+            self.offset_at(tri[0]),
+            self.offset_at(tri[1]),
+            self.offset_at(tri[2]),
+        ];
+        let lo = offsets[0].min(offsets[1]).min(offsets[2]);
+        let hi = offsets[0].max(offsets[1]).max(offsets[2]);
+        // The offset is linear, so the triangle is cut at stop offsets.
+        let mut rest: Vec<Vec2d> = tri.to_vec();
+        let mut cut = Scalar::NEG_INFINITY;
+        for stop in &self.stops {
+            if stop.offset <= lo || stop.offset >= hi || stop.offset == cut {
+                continue;
+            }
+            cut = stop.offset;
+            let (below, above) = self.cut(&rest, cut);
+            self.fan(&below, emit);
+            rest = above;
+        }
+        self.fan(&rest, emit);
+    }
+
+    /// Cuts a convex polygon into the parts below and above an offset.
+    fn cut(&self, polygon: &[Vec2d], offset: Scalar) -> (Vec<Vec2d>, Vec<Vec2d>) {
+        let (mut below, mut above) = (vec![], vec![]);
+        let n = polygon.len();
+        for i in 0..n {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            let (ta, tb) = (self.offset_at(a), self.offset_at(b));
+            if ta <= offset {
+                below.push(a);
+            }
+            if ta >= offset {
+                above.push(a);
+            }
+            if (ta < offset && tb > offset) || (ta > offset && tb < offset) {
+                let s = (offset - ta) / (tb - ta);
+                let p = [a[0] + (b[0] - a[0]) * s, a[1] + (b[1] - a[1]) * s];
+                below.push(p);
+                above.push(p);
+            }
+        }
+        (below, above)
+    }
+
+    /// Returns the offset at a position, as close as possible to a reference.
+    ///
+    /// Conic gradients repeat every turn,
+    /// so this avoids interpolating across the start angle.
+    fn offset_near(&self, pos: Vec2d, reference: Scalar) -> Scalar {
+        let offset = self.offset_at(pos);
+        match self.kind {
+            Kind::Conic { .. } => offset + (reference - offset).round(),
+            _ => offset,
+        }
+    }
+
+    /// Subdivides a triangle until the colors are close to linear.
+    ///
+    /// Triangles are also subdivided where the color changes abruptly,
+    /// to keep the change sharp.
+    /// Subdivision stops when the triangle is about a pixel in size.
+    fn subdivide(
+        &self,
+        tri: [Vec2d; 3],
+        transform: Matrix2d,
+        depth: u32,
+        emit: &mut dyn FnMut([Vec2d; 3], [Color; 3]),
+    ) {
+        let centroid = mul_scalar(add(add(tri[0], tri[1]), tri[2]), 1.0 / 3.0);
+        let reference = self.offset_at(centroid);
+        let offsets = [
+            self.offset_near(tri[0], reference),
+            self.offset_near(tri[1], reference),
+            self.offset_near(tri[2], reference),
+        ];
+        let color = |k: usize| self.color_between_stops(offsets[k], reference);
+        let colors = [color(0), color(1), color(2)];
+        if depth == 0 || extent(tri, transform) <= MIN_EXTENT {
+            emit(tri, colors);
+            return;
+        }
+
+        let mids = [
+            mul_scalar(add(tri[0], tri[1]), 0.5),
+            mul_scalar(add(tri[1], tri[2]), 0.5),
+            mul_scalar(add(tri[2], tri[0]), 0.5),
+        ];
+        // Compares the exact colors with the interpolated colors.
+        let error = |pos: Vec2d, weights: [f32; 3]| {
+            let exact = self.color_at_offset(self.offset_at(pos));
+            (0..4)
+                .map(|c| {
+                    let linear: f32 = (0..3).map(|k| colors[k][c] * weights[k]).sum();
+                    (exact[c] - linear).abs()
+                })
+                .fold(0.0, f32::max)
+        };
+        let third = 1.0 / 3.0;
+        let error = error(centroid, [third; 3])
+            .max(error(mids[0], [0.5, 0.5, 0.0]))
+            .max(error(mids[1], [0.0, 0.5, 0.5]))
+            .max(error(mids[2], [0.5, 0.0, 0.5]));
+        let lo = offsets[0].min(offsets[1]).min(offsets[2]);
+        let hi = offsets[0].max(offsets[1]).max(offsets[2]);
+        if error <= COLOR_TOLERANCE && !self.is_abrupt(lo, hi) {
+            emit(tri, colors);
+            return;
+        }
+        self.subdivide([tri[0], mids[0], mids[2]], transform, depth - 1, emit);
+        self.subdivide([mids[0], tri[1], mids[1]], transform, depth - 1, emit);
+        self.subdivide([mids[2], mids[1], tri[2]], transform, depth - 1, emit);
+        self.subdivide([mids[0], mids[1], mids[2]], transform, depth - 1, emit);
+    }
+
+    /// Returns whether the color changes abruptly between two offsets.
+    ///
+    /// This happens at stops with the same offset and different colors,
+    /// and for conic gradients at the start angle
+    /// when the first and last colors are different.
+    fn is_abrupt(&self, lo: Scalar, hi: Scalar) -> bool {
+        let hard_stop = self.stops.windows(2).any(|w| {
+            w[0].offset == w[1].offset
+                && w[0].color != w[1].color
+                && lo < w[0].offset
+                && w[0].offset < hi
+        });
+        let seam = matches!(self.kind, Kind::Conic { .. })
+            && lo.floor() + 1.0 < hi
+            && self.color_at_offset(0.0) != self.color_at_offset(1.0);
+        hard_stop || seam
+    }
+
+    /// Emits a convex polygon between two stops as a triangle fan.
+    fn fan(&self, polygon: &[Vec2d], emit: &mut dyn FnMut([Vec2d; 3], [Color; 3])) {
+        if polygon.len() < 3 {
+            return;
+        }
+        let offsets: Vec<Scalar> = polygon.iter().map(|&p| self.offset_at(p)).collect();
+        let reference = offsets.iter().sum::<Scalar>() / offsets.len() as Scalar;
+        let color = |k: usize| self.color_between_stops(offsets[k], reference);
+        for i in 2..polygon.len() {
+            emit(
+                [polygon[0], polygon[i - 1], polygon[i]],
+                [color(0), color(i - 1), color(i)],
+            );
+        }
+    }
+}
+
+/// Returns the largest side of the bounding box of a triangle after transform.
+fn extent(tri: [Vec2d; 3], transform: Matrix2d) -> f32 {
+    let x = |k: usize| tx(transform, tri[k][0], tri[k][1]);
+    let y = |k: usize| ty(transform, tri[k][0], tri[k][1]);
+    let (x0, x1, x2) = (x(0), x(1), x(2));
+    let (y0, y1, y2) = (y(0), y(1), y(2));
+    let w = x0.max(x1).max(x2) - x0.min(x1).min(x2);
+    let h = y0.max(y1).max(y2) - y0.min(y1).min(y2);
+    w.max(h)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{software::Canvas, Context, Rectangle};
+
+    #[test]
+    fn test_color_at() {
+        let gradient = Gradient::linear([10.0, 0.0], [20.0, 0.0])
+            .stop(1.0, [0.0, 0.0, 1.0, 1.0])
+            .stop(0.0, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(gradient.stops[0].offset, 0.0);
+        assert_eq!(gradient.color_at([0.0, 5.0]), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(gradient.color_at([15.0, 5.0]), [0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(gradient.color_at([30.0, 5.0]), [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(
+            Gradient::linear([0.0; 2], [1.0; 2]).color_at([0.0; 2]),
+            [0.0; 4]
+        );
+        assert_eq!(gradient.stop(f64::NAN, [1.0; 4]).stops.len(), 2);
+    }
+
+    #[test]
+    fn test_linear() {
+        let c = Context::new_abs(40.0, 10.0);
+        // Hard stop in the middle.
+        let gradient = Gradient::linear([0.0, 0.0], [40.0, 0.0])
+            .stop(0.0, [1.0, 0.0, 0.0, 1.0])
+            .stop(0.5, [0.0, 1.0, 0.0, 1.0])
+            .stop(0.5, [0.0, 0.0, 1.0, 1.0])
+            .stop(1.0, [1.0, 1.0, 1.0, 1.0]);
+        let mut canvas = Canvas::new(40, 10);
+        Rectangle::new([1.0; 4]).draw_gradient(
+            &gradient,
+            [0.0, 0.0, 40.0, 10.0],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        for x in 0..40 {
+            let expected = gradient.color_at([x as Scalar + 0.5, 0.0]);
+            let pixel = canvas.pixel(x, 5);
+            for k in 0..4 {
+                let diff = (pixel[k] as f32 - expected[k] * 255.0).abs();
+                assert!(diff <= 2.0, "{} {:?} {:?}", x, pixel, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_radial_conic_offset() {
+        let radial = Gradient::radial([10.0, 10.0], 10.0);
+        assert_eq!(radial.offset_at([10.0, 10.0]), 0.0);
+        assert!((radial.offset_at([15.0, 10.0]) - 0.5).abs() < 1e-9);
+        assert!((radial.offset_at([10.0, 0.0]) - 1.0).abs() < 1e-9);
+        let focal = radial.focal([15.0, 10.0]);
+        assert!(focal.offset_at([15.0, 10.0]).abs() < 1e-9);
+        assert!((focal.offset_at([20.0, 10.0]) - 1.0).abs() < 1e-9);
+        assert!((focal.offset_at([0.0, 10.0]) - 1.0).abs() < 1e-9);
+        assert!((focal.offset_at([10.0, 10.0]) - 1.0 / 3.0).abs() < 1e-9);
+        // Focal points outside the circle are moved inside.
+        let outside = Gradient::radial([0.0, 0.0], 1.0).focal([2.0, 0.0]);
+        if let Kind::Radial { focal, .. } = outside.kind {
+            assert!((focal[0] - MAX_FOCAL).abs() < 1e-9);
+        }
+
+        let conic = Gradient::conic([0.0, 0.0], 0.0);
+        assert_eq!(conic.offset_at([1.0, 0.0]), 0.0);
+        assert!((conic.offset_at([0.0, 1.0]) - 0.25).abs() < 1e-9);
+        assert!((conic.offset_at([0.0, -1.0]) - 0.75).abs() < 1e-9);
+        let rotated = Gradient::conic([0.0, 0.0], <Scalar as Radians>::_90());
+        assert!(rotated.offset_at([1.0, 0.0]) > 0.74);
+    }
+
+    #[test]
+    fn test_radial_conic() {
+        use crate::Ellipse;
+
+        let c = Context::new_abs(40.0, 40.0);
+        let radial = Gradient::radial([20.0, 20.0], 20.0)
+            .focal([25.0, 20.0])
+            .stop(0.0, [1.0, 1.0, 1.0, 1.0])
+            .stop(1.0, [0.0, 0.0, 1.0, 1.0]);
+        let conic = Gradient::conic([20.0, 20.0], 0.0)
+            .stop(0.0, [1.0, 0.0, 0.0, 1.0])
+            .stop(0.5, [0.0, 1.0, 0.0, 1.0])
+            .stop(1.0, [0.0, 0.0, 1.0, 1.0]);
+        for gradient in &[radial, conic] {
+            let mut canvas = Canvas::new(40, 40);
+            Ellipse::new([1.0; 4]).draw_gradient(
+                gradient,
+                [0.0, 0.0, 40.0, 40.0],
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+            for y in (4..36).step_by(3) {
+                for x in (4..36).step_by(3) {
+                    let pos = [x as Scalar + 0.5, y as Scalar + 0.5];
+                    let d = [pos[0] - 20.0, pos[1] - 20.0];
+                    // Skip the edge, the center and the start angle of the conic gradient.
+                    let r2 = square_len(d);
+                    if !(4.0..=18.0 * 18.0).contains(&r2) || (d[0] > 0.0 && d[1].abs() < 2.0) {
+                        continue;
+                    }
+                    let expected = gradient.color_at(pos);
+                    let pixel = canvas.pixel(x, y);
+                    for k in 0..4 {
+                        let diff = (pixel[k] as f32 - expected[k] * 255.0).abs();
+                        assert!(diff <= 4.0, "{} {} {:?} {:?}", x, y, pixel, expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_subdivision_cost() {
+        use crate::{
+            display_list::{Command, DisplayList},
+            software::Texture,
+            Transformed,
+        };
+
+        let c = Context::new_abs(1000.0, 1000.0);
+        let triangles = |gradient: &Gradient, size: Scalar| {
+            let mut list: DisplayList<Texture> = DisplayList::new();
+            Rectangle::new([1.0; 4]).draw_gradient(
+                gradient,
+                [0.0, 0.0, 1.0, 1.0],
+                &c.draw_state,
+                c.transform.scale(size, size),
+                &mut list,
+            );
+            list.commands
+                .iter()
+                .map(|c| match c {
+                    Command::TriListC { vertices, .. } => vertices.len() / 3,
+                    _ => 0,
+                })
+                .sum::<usize>()
+        };
+        let smooth = Gradient::radial([0.5, 0.5], 0.5)
+            .stop(0.0, [1.0; 4])
+            .stop(1.0, [0.0, 0.0, 1.0, 1.0]);
+        let hard = smooth
+            .clone()
+            .stop(0.5, [1.0; 4])
+            .stop(0.5, [0.0, 0.0, 1.0, 1.0]);
+        // Smooth ramps are limited by the color error.
+        assert_eq!(triangles(&smooth, 100.0), triangles(&smooth, 1000.0));
+        // Hard stops are limited by the size on screen.
+        assert!(triangles(&hard, 1.0) < 50);
+        assert!(triangles(&hard, 1000.0) < 10 * triangles(&hard, 100.0));
+    }
+}
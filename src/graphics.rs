@@ -1,8 +1,29 @@
 use crate::{
     types::{self, Matrix2d, Scalar},
     CircleArc, DrawState, Ellipse, Image, ImageSize, Line, Polygon, Rectangle,
+    BACK_END_MAX_VERTEX_COUNT as BUFFER_SIZE,
 };
 
+/// Implemented by integer types used as vertex indices.
+pub trait VertexIndex: Copy {
+    /// Converts to an index into a slice of vertices.
+    fn to_usize(self) -> usize;
+}
+
+impl VertexIndex for u16 {
+    #[inline(always)]
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl VertexIndex for u32 {
+    #[inline(always)]
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
 /// Implemented by all graphics back-ends.
 ///
 /// [An example back-end using raw OpenGL](https://github.com/PistonDevelopers/opengl_graphics)
@@ -136,6 +157,142 @@ pub trait Graphics: Sized {
     ) where
         F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]]));
 
+    /// Renders list of indexed 2d triangles using a solid color.
+    ///
+    /// Works like `tri_list`, but each chunk contains vertices and indices.
+    /// Every 3 indices form a triangle of vertices in the same chunk.
+    /// Shared vertices are only sent once, which reduces vertex traffic
+    /// for back-ends that support index buffers.
+    /// The number of vertices per chunk never exceeds
+    /// `BACK_END_MAX_VERTEX_COUNT`.
+    ///
+    /// Arguments are `|vertices: &[[f32; 2]], indices: &[I]|`.
+    ///
+    /// Can be overridden in the back-end for higher performance.
+    /// By default, the indices are expanded into vertices for `tri_list`.
+    fn tri_list_indexed<I, F>(&mut self, draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        I: VertexIndex,
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[I])),
+    {
+        self.tri_list(draw_state, color, |g| {
+            let mut buffer = [[0.0; 2]; BUFFER_SIZE];
+            let mut n = 0;
+            f(&mut |vertices, indices| {
+                for &i in indices {
+                    buffer[n] = vertices[i.to_usize()];
+                    n += 1;
+                    if n == BUFFER_SIZE {
+                        g(&buffer);
+                        n = 0;
+                    }
+                }
+            });
+            if n > 0 {
+                g(&buffer[..n]);
+            }
+        });
+    }
+
+    /// Same as `tri_list_indexed`, but with individual vertex colors.
+    ///
+    /// Arguments are `|vertices: &[[f32; 2]], colors: &[[f32; 4]], indices: &[I]|`.
+    fn tri_list_c_indexed<I, F>(&mut self, draw_state: &DrawState, mut f: F)
+    where
+        I: VertexIndex,
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]], &[I])),
+    {
+        self.tri_list_c(draw_state, |g| {
+            let mut buffer = [[0.0; 2]; BUFFER_SIZE];
+            let mut colors = [[0.0; 4]; BUFFER_SIZE];
+            let mut n = 0;
+            f(&mut |vertices, vertex_colors, indices| {
+                for &i in indices {
+                    buffer[n] = vertices[i.to_usize()];
+                    colors[n] = vertex_colors[i.to_usize()];
+                    n += 1;
+                    if n == BUFFER_SIZE {
+                        g(&buffer, &colors);
+                        n = 0;
+                    }
+                }
+            });
+            if n > 0 {
+                g(&buffer[..n], &colors[..n]);
+            }
+        });
+    }
+
+    /// Same as `tri_list_uv`, but with indexed vertices.
+    ///
+    /// Arguments are `|vertices: &[[f32; 2]], texture_coords: &[[f32; 2]], indices: &[I]|`.
+    fn tri_list_uv_indexed<I, F>(
+        &mut self,
+        draw_state: &DrawState,
+        color: &[f32; 4],
+        texture: &<Self as Graphics>::Texture,
+        mut f: F,
+    ) where
+        I: VertexIndex,
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[I])),
+    {
+        self.tri_list_uv(draw_state, color, texture, |g| {
+            let mut buffer = [[0.0; 2]; BUFFER_SIZE];
+            let mut uvs = [[0.0; 2]; BUFFER_SIZE];
+            let mut n = 0;
+            f(&mut |vertices, texture_coords, indices| {
+                for &i in indices {
+                    buffer[n] = vertices[i.to_usize()];
+                    uvs[n] = texture_coords[i.to_usize()];
+                    n += 1;
+                    if n == BUFFER_SIZE {
+                        g(&buffer, &uvs);
+                        n = 0;
+                    }
+                }
+            });
+            if n > 0 {
+                g(&buffer[..n], &uvs[..n]);
+            }
+        });
+    }
+
+    /// Same as `tri_list_uv_c`, but with indexed vertices.
+    ///
+    /// Arguments are
+    /// `|vertices: &[[f32; 2]], texture_coords: &[[f32; 2]], colors: &[[f32; 4]], indices: &[I]|`.
+    fn tri_list_uv_c_indexed<I, F>(
+        &mut self,
+        draw_state: &DrawState,
+        texture: &<Self as Graphics>::Texture,
+        mut f: F,
+    ) where
+        I: VertexIndex,
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]], &[I])),
+    {
+        self.tri_list_uv_c(draw_state, texture, |g| {
+            let mut buffer = [[0.0; 2]; BUFFER_SIZE];
+            let mut uvs = [[0.0; 2]; BUFFER_SIZE];
+            let mut colors = [[0.0; 4]; BUFFER_SIZE];
+            let mut n = 0;
+            f(&mut |vertices, texture_coords, vertex_colors, indices| {
+                for &i in indices {
+                    buffer[n] = vertices[i.to_usize()];
+                    uvs[n] = texture_coords[i.to_usize()];
+                    colors[n] = vertex_colors[i.to_usize()];
+                    n += 1;
+                    if n == BUFFER_SIZE {
+                        g(&buffer, &uvs, &colors);
+                        n = 0;
+                    }
+                }
+            });
+            if n > 0 {
+                g(&buffer[..n], &uvs[..n], &colors[..n]);
+            }
+        });
+    }
+
     /// Draws a rectangle.
     ///
     /// Can be overriden in the back-end for higher performance.
@@ -168,6 +325,22 @@ pub trait Graphics: Sized {
         p.draw_tri(polygon, draw_state, transform, self);
     }
 
+    /// Draws a polygon with multiple contours.
+    ///
+    /// Can be overridden in the back-end for higher performance.
+    ///
+    /// Instead of calling this directly, use `Polygon::draw_contours`.
+    #[inline(always)]
+    fn polygon_contours(
+        &mut self,
+        p: &Polygon,
+        contours: types::Contours<'_>,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+    ) {
+        p.draw_contours_tri(contours, draw_state, transform, self);
+    }
+
     /// Draws a tweened polygon using linear interpolation.
     ///
     /// Can be overridden in the back-end for higher performance.
@@ -233,6 +406,23 @@ pub trait Graphics: Sized {
         l.draw_tri(line, draw_state, transform, self);
     }
 
+    /// Draws connected line segments.
+    ///
+    /// Can be overridden in the back-end for higher performance.
+    ///
+    /// Instead of calling this directly, use `Line::draw_polyline`.
+    #[inline(always)]
+    fn polyline(
+        &mut self,
+        l: &Line,
+        points: types::Polygon<'_>,
+        closed: bool,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+    ) {
+        l.draw_polyline_tri(points, closed, draw_state, transform, self);
+    }
+
     /// Draws circle arc.
     ///
     /// Can be overriden in the back-end for higher performance.
@@ -47,6 +47,37 @@ impl Grid {
         }
     }
 
+    /// Draws lines dividing each cell into smaller cells.
+    ///
+    /// Lines on the grid are skipped, so a different line,
+    /// e.g. a thinner or dashed line, can be used for the minor lines.
+    pub fn draw_minor<G>(
+        &self,
+        line: &Line,
+        subdivisions: u32,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let &Grid { cols, rows, units } = self;
+        let step = units / subdivisions as Scalar;
+        let (w, h) = (cols as Scalar * units, rows as Scalar * units);
+        for x in 0..cols * subdivisions {
+            if x % subdivisions != 0 {
+                let x1 = x as Scalar * step;
+                line.draw([x1, 0.0, x1, h], draw_state, transform, g);
+            }
+        }
+        for y in 0..rows * subdivisions {
+            if y % subdivisions != 0 {
+                let y1 = y as Scalar * step;
+                line.draw([0.0, y1, w, y1], draw_state, transform, g);
+            }
+        }
+    }
+
     /// Get a GridIterator for the grid
     pub fn cells(&self) -> GridCells {
         GridCells {
@@ -132,4 +163,21 @@ mod tests {
         assert_eq!(4.0, g.x_pos((2, 3)));
         assert_eq!(6.0, g.y_pos((2, 3)));
     }
+
+    #[test]
+    fn test_draw_minor() {
+        use crate::{display_list::DisplayList, software::Texture, Context};
+
+        let c = Context::new();
+        let grid = Grid {
+            cols: 2,
+            rows: 1,
+            units: 10.0,
+        };
+        let mut list: DisplayList<Texture> = DisplayList::new();
+        let line = Line::new([1.0; 4], 0.5);
+        grid.draw_minor(&line, 2, &c.draw_state, c.transform, &mut list);
+        // Two vertical lines and one horizontal line between the grid lines.
+        assert_eq!(list.commands.len(), 3);
+    }
 }
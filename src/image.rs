@@ -1,12 +1,18 @@
 //! Draw an image
 
 use crate::{
-    math::Matrix2d,
+    math::{Matrix2d, Scalar, Vec2d},
     triangulation,
     types::{Color, Rectangle, SourceRectangle},
-    DrawState, Graphics, ImageSize,
+    DrawState, Graphics, ImageSize, BACK_END_MAX_VERTEX_COUNT as BUFFER_SIZE,
 };
 
+/// The number of cells along each side when drawing a quad in perspective.
+const QUAD_SUBDIVISIONS: usize = 16;
+
+/// The number of cells along each side of a deformation grid cell.
+const GRID_SUBDIVISIONS: usize = 4;
+
 /// An image
 ///
 /// # Example
@@ -142,8 +148,6 @@ impl Image {
     ) where
         G: Graphics,
     {
-        use crate::math::Scalar;
-
         let color = self.color.unwrap_or([1.0; 4]);
         let source_rectangle = self.source_rectangle.unwrap_or({
             let (w, h) = texture.get_size();
@@ -162,6 +166,220 @@ impl Image {
             )
         });
     }
+
+    /// Draws image mapped onto a quad.
+    ///
+    /// The corners are in the order upper left, upper right,
+    /// lower right and lower left of the image.
+    /// The image is drawn in perspective when the quad is convex,
+    /// and interpolated bilinearly otherwise.
+    /// The rectangle of the image is ignored.
+    pub fn draw_quad<G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        corners: [Vec2d; 4],
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let n = QUAD_SUBDIVISIONS;
+        match homography(corners) {
+            Some(m) => self.draw_warped(texture, draw_state, transform, g, [n, n], |u, v| {
+                let w = m[2][0] * u + m[2][1] * v + 1.0;
+                [
+                    (m[0][0] * u + m[0][1] * v + m[0][2]) / w,
+                    (m[1][0] * u + m[1][1] * v + m[1][2]) / w,
+                ]
+            }),
+            None => self.draw_warped(texture, draw_state, transform, g, [n, n], |u, v| {
+                bilinear(corners, u, v)
+            }),
+        }
+    }
+
+    /// Draws image mapped onto a deformation grid.
+    ///
+    /// The grid points are stored row by row, with `columns` points per row.
+    /// The image is spread evenly over the grid cells,
+    /// and interpolated bilinearly inside each cell.
+    /// The rectangle of the image is ignored.
+    pub fn draw_grid<G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        points: &[Vec2d],
+        columns: usize,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        if columns < 2 || points.len() < 2 * columns {
+            return;
+        }
+        let rows = points.len() / columns;
+        let n = [
+            (columns - 1) * GRID_SUBDIVISIONS,
+            (rows - 1) * GRID_SUBDIVISIONS,
+        ];
+        self.draw_warped(texture, draw_state, transform, g, n, |u, v| {
+            let (x, y) = (u * (columns - 1) as Scalar, v * (rows - 1) as Scalar);
+            let (i, j) = ((x as usize).min(columns - 2), (y as usize).min(rows - 2));
+            let p = |i: usize, j: usize| points[j * columns + i];
+            bilinear(
+                [p(i, j), p(i + 1, j), p(i + 1, j + 1), p(i, j + 1)],
+                x - i as Scalar,
+                y - j as Scalar,
+            )
+        });
+    }
+
+    /// Draws image split into cells, where `pos` maps
+    /// coordinates in the image from 0 to 1 to positions.
+    fn draw_warped<P, G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+        cells: [usize; 2],
+        pos: P,
+    ) where
+        P: Fn(Scalar, Scalar) -> Vec2d,
+        G: Graphics,
+    {
+        let color = self.color.unwrap_or([1.0; 4]);
+        let (w, h) = texture.get_size();
+        let (w, h) = (w as Scalar, h as Scalar);
+        let src = self.source_rectangle.unwrap_or([0.0, 0.0, w, h]);
+        g.tri_list_uv(draw_state, &color, texture, |f| {
+            let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            let mut uvs: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            let mut i = 0;
+            let [nu, nv] = cells;
+            let mut row: Vec<([f32; 2], [f32; 2])> = vec![];
+            let mut next_row = vec![];
+            for j in 0..=nv {
+                let v = j as Scalar / nv as Scalar;
+                next_row.clear();
+                next_row.extend((0..=nu).map(|k| {
+                    let u = k as Scalar / nu as Scalar;
+                    let p = pos(u, v);
+                    (
+                        [
+                            triangulation::tx(transform, p[0], p[1]),
+                            triangulation::ty(transform, p[0], p[1]),
+                        ],
+                        [
+                            ((src[0] + u * src[2]) / w) as f32,
+                            ((src[1] + v * src[3]) / h) as f32,
+                        ],
+                    )
+                }));
+                if j > 0 {
+                    for k in 0..nu {
+                        let quad = [row[k], row[k + 1], next_row[k], next_row[k + 1]];
+                        for &q in &[0, 1, 2, 1, 3, 2] {
+                            vertices[i] = quad[q].0;
+                            uvs[i] = quad[q].1;
+                            i += 1;
+                            // The buffer size is a multiple of 3.
+                            if i == BUFFER_SIZE {
+                                f(&vertices, &uvs);
+                                i = 0;
+                            }
+                        }
+                    }
+                }
+                std::mem::swap(&mut row, &mut next_row);
+            }
+            if i > 0 {
+                f(&vertices[0..i], &uvs[0..i]);
+            }
+        });
+    }
+
+    /// Draws triangles textured with the image.
+    ///
+    /// The rectangle of the image is mapped to the source rectangle,
+    /// and texture coordinates are computed for every vertex.
+    /// `triangles` streams the triangles in coordinates before transform,
+    /// in the same way as the closures passed to `Graphics::tri_list`.
+    pub fn draw_tri_list<F, G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+        mut triangles: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+        G: Graphics,
+    {
+        let color = self.color.unwrap_or([1.0; 4]);
+        let (w, h) = texture.get_size();
+        let (w, h) = (w as Scalar, h as Scalar);
+        let src = self.source_rectangle.unwrap_or([0.0, 0.0, w, h]);
+        let rect = self.rectangle.unwrap_or([0.0, 0.0, src[2], src[3]]);
+        if rect[2] == 0.0 || rect[3] == 0.0 {
+            return;
+        }
+        g.tri_list_uv(draw_state, &color, texture, |f| {
+            let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            let mut uvs: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            triangles(&mut |tris| {
+                for chunk in tris.chunks(BUFFER_SIZE) {
+                    let n = chunk.len();
+                    for (i, p) in chunk.iter().enumerate() {
+                        let (x, y) = (p[0] as Scalar, p[1] as Scalar);
+                        vertices[i] = [
+                            triangulation::tx(transform, x, y),
+                            triangulation::ty(transform, x, y),
+                        ];
+                        uvs[i] = [
+                            ((src[0] + (x - rect[0]) / rect[2] * src[2]) / w) as f32,
+                            ((src[1] + (y - rect[1]) / rect[3] * src[3]) / h) as f32,
+                        ];
+                    }
+                    f(&vertices[0..n], &uvs[0..n]);
+                }
+            });
+        });
+    }
+}
+
+/// Returns the projective transform from the unit square to a quad.
+///
+/// Returns `None` when the quad is not convex.
+fn homography(corners: [Vec2d; 4]) -> Option<[[Scalar; 3]; 3]> {
+    let [p0, p1, p2, p3] = corners;
+    let (dx1, dy1) = (p1[0] - p2[0], p1[1] - p2[1]);
+    let (dx2, dy2) = (p3[0] - p2[0], p3[1] - p2[1]);
+    let (dx3, dy3) = (p0[0] - p1[0] + p2[0] - p3[0], p0[1] - p1[1] + p2[1] - p3[1]);
+    let den = dx1 * dy2 - dx2 * dy1;
+    if den == 0.0 {
+        return None;
+    }
+    let g = (dx3 * dy2 - dx2 * dy3) / den;
+    let h = (dx1 * dy3 - dx3 * dy1) / den;
+    // The quad is convex when the corners stay in front of the projection.
+    if 1.0 + g <= 0.0 || 1.0 + h <= 0.0 || 1.0 + g + h <= 0.0 {
+        return None;
+    }
+    Some([
+        [p1[0] - p0[0] + g * p1[0], p3[0] - p0[0] + h * p3[0], p0[0]],
+        [p1[1] - p0[1] + g * p1[1], p3[1] - p0[1] + h * p3[1], p0[1]],
+        [g, h, 1.0],
+    ])
+}
+
+/// Interpolates bilinearly between the corners of a quad.
+fn bilinear(corners: [Vec2d; 4], u: Scalar, v: Scalar) -> Vec2d {
+    let [p0, p1, p2, p3] = corners;
+    let lerp = |a: Vec2d, b: Vec2d, t: Scalar| [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+    lerp(lerp(p0, p1, u), lerp(p3, p2, u), v)
 }
 
 impl  Default for Image {
@@ -193,7 +411,11 @@ pub fn draw_many<G>(
 
 #[cfg(test)]
 mod test {
-    use super::Image;
+    use super::*;
+    use crate::{
+        software::{Canvas, Texture},
+        Context,
+    };
 
     #[test]
     fn test_image() {
@@ -202,4 +424,78 @@ mod test {
             .rect([0.0, 0.0, 100.0, 100.0])
             .src_rect([0.0, 0.0, 32.0, 32.0]);
     }
+
+    #[test]
+    fn test_homography() {
+        let corners = [[0.0, 0.0], [40.0, 10.0], [40.0, 30.0], [0.0, 40.0]];
+        let m = homography(corners).unwrap();
+        let map = |u: Scalar, v: Scalar| {
+            let w = m[2][0] * u + m[2][1] * v + 1.0;
+            [
+                (m[0][0] * u + m[0][1] * v + m[0][2]) / w,
+                (m[1][0] * u + m[1][1] * v + m[1][2]) / w,
+            ]
+        };
+        for (i, &(u, v)) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+            .iter()
+            .enumerate()
+        {
+            let p = map(u, v);
+            assert!((p[0] - corners[i][0]).abs() < 1e-9 && (p[1] - corners[i][1]).abs() < 1e-9);
+        }
+        // The center maps to the intersection of the diagonals.
+        let center = map(0.5, 0.5);
+        assert!((center[0] - 80.0 / 3.0).abs() < 1e-9);
+        assert!((center[1] - 20.0).abs() < 1e-9);
+        // Not convex.
+        assert!(homography([[0.0, 0.0], [10.0, 0.0], [2.0, 2.0], [0.0, 10.0]]).is_none());
+    }
+
+    #[test]
+    fn test_draw_quad_grid() {
+        // White left half and black right half.
+        let mut pixels = vec![];
+        for p in &[[255; 4], [0, 0, 0, 255], [255; 4], [0, 0, 0, 255]] {
+            pixels.extend_from_slice(p);
+        }
+        let texture = Texture::from_rgba8(2, 2, pixels).unwrap();
+        let c = Context::new_abs(40.0, 40.0);
+        let white = [255; 4];
+        let black = [0, 0, 0, 255];
+
+        let mut canvas = Canvas::new(40, 40);
+        Image::new().draw_quad(
+            &texture,
+            [[0.0, 0.0], [40.0, 10.0], [40.0, 30.0], [0.0, 40.0]],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        // The middle of the image moves towards the shorter side.
+        assert_eq!(canvas.pixel(24, 20), white);
+        assert_eq!(canvas.pixel(29, 20), black);
+        assert_eq!(canvas.pixel(2, 20), white);
+        assert_eq!(canvas.pixel(38, 3), [0; 4]);
+
+        // A grid with the middle column moved right.
+        let mut canvas = Canvas::new(40, 40);
+        let points = [
+            [0.0, 0.0],
+            [30.0, 0.0],
+            [40.0, 0.0],
+            [0.0, 40.0],
+            [30.0, 40.0],
+            [40.0, 40.0],
+        ];
+        Image::new().draw_grid(
+            &texture,
+            &points,
+            3,
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(28, 20), white);
+        assert_eq!(canvas.pixel(32, 20), black);
+    }
 }
@@ -41,14 +41,16 @@ pub use colored::Colored;
 pub use context::Context;
 pub use draw_state::DrawState;
 pub use ellipse::Ellipse;
-pub use crate::graphics::Graphics;
+pub use crate::graphics::{Graphics, VertexIndex};
 pub use image::Image;
 pub use line::Line;
+pub use nine_patch::NinePatch;
 pub use polygon::Polygon;
 pub use radians::Radians;
 pub use rectangle::Rectangle;
 pub use rectangled::Rectangled;
 pub use source_rectangled::SourceRectangled;
+pub use sprite::Sprite;
 pub use text::Text;
 pub use texture::ImageSize;
 pub use transformed::Transformed;
@@ -68,21 +70,34 @@ mod rectangled;
 mod source_rectangled;
 mod transformed;
 
+pub mod animation;
+pub mod batch;
+pub mod bounds;
 pub mod character;
 pub mod circle_arc;
 pub mod color;
 pub mod context;
+pub mod display_list;
 pub mod draw_state;
 pub mod ellipse;
 pub mod glyph_cache;
+pub mod gradient;
 pub mod grid;
 pub mod image;
 pub mod line;
 pub mod math;
 pub mod modular_index;
+pub mod nine_patch;
+pub mod path;
+pub mod pattern;
 pub mod polygon;
 pub mod rectangle;
+pub mod rich_text;
+pub mod software;
+pub mod sprite;
+pub mod svg;
 pub mod text;
+pub mod text_layout;
 pub mod texture_packer;
 pub mod triangulation;
 pub mod types;
@@ -1,17 +1,22 @@
 //! Draw Line
 
 use crate::{
-    math::{Matrix2d, Scalar},
+    math::{Matrix2d, Scalar, Vec2d},
+    path::{self, Path},
     triangulation, types,
     types::{Color, Radius, Resolution},
     DrawState, Graphics,
 };
 
 /// The shape of the line
+///
+/// For polylines, this is the shape of the caps at both ends.
 #[derive(Copy, Clone)]
 pub enum Shape {
     /// Square edges
     Square,
+    /// Square edges extending half the line width beyond the end points
+    ProjectingSquare,
     /// Round edges
     Round,
     /// Round edges with specified resolution
@@ -22,11 +27,11 @@ pub enum Shape {
 
 impl Shape {
     /// Gets the round resolution of the shape.
-    fn resolution(&self) -> u32 {
+    pub(crate) fn resolution(&self) -> u32 {
         use Shape::*;
 
         match self {
-            Square => 2,
+            Square | ProjectingSquare => 2,
             Bevel => 3,
             Round => 64,
             RoundWithResolution(n) => *n,
@@ -34,6 +39,101 @@ impl Shape {
     }
 }
 
+/// The shape of the corners between segments of a polyline
+#[derive(Copy, Clone)]
+pub enum Join {
+    /// Sharp corners extending the outer edges until they meet
+    ///
+    /// Falls back to bevel when the ratio between the length of the corner
+    /// and the line width exceeds the limit.
+    Miter(Scalar),
+    /// Round corners
+    Round,
+    /// Corners cut off by a straight edge
+    Bevel,
+}
+
+/// A dash pattern of alternating dashes and gaps
+///
+/// Use a dash length of zero with round caps to draw dots.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Dash {
+    /// The length of each dash
+    pub dash: Scalar,
+    /// The length of each gap
+    pub gap: Scalar,
+    /// The distance into the pattern at the start of the line
+    pub offset: Scalar,
+}
+
+impl Dash {
+    /// Creates a new dash pattern.
+    pub fn new(dash: Scalar, gap: Scalar) -> Dash {
+        Dash {
+            dash,
+            gap,
+            offset: 0.0,
+        }
+    }
+
+    /// Sets offset.
+    pub fn offset(mut self, value: Scalar) -> Self {
+        self.offset = value;
+        self
+    }
+
+    /// Splits connected line segments into dashes.
+    ///
+    /// When `closed` is `true`, the last point connects to the first point.
+    /// Returns a list of points for each dash.
+    /// Dashes of zero length contain the same point twice.
+    pub fn split(&self, points: types::Polygon<'_>, closed: bool) -> Vec<Vec<Vec2d>> {
+        let period = self.dash + self.gap;
+        if points.is_empty() || self.dash < 0.0 || self.gap < 0.0 || period <= 0.0 {
+            return vec![points.to_vec()];
+        }
+        let mut res = vec![];
+        let pos = self.offset.rem_euclid(period);
+        // The pattern starts with a dash, even when it has zero length.
+        let mut on = pos < self.dash || pos == 0.0;
+        // Distance until toggling between dash and gap.
+        let mut remaining = if on { self.dash - pos } else { period - pos };
+        let mut current = if on { vec![points[0]] } else { vec![] };
+        let n = points.len();
+        let segments = if closed { n } else { n - 1 };
+        for i in 0..segments {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+            let len = (dx * dx + dy * dy).sqrt();
+            let mut s = 0.0;
+            while len - s > remaining {
+                s += remaining;
+                let p = [a[0] + dx * s / len, a[1] + dy * s / len];
+                if on {
+                    // Dashes ending at a corner already contain the point.
+                    if current.len() < 2 || current[current.len() - 1] != p {
+                        current.push(p);
+                    }
+                    res.push(std::mem::take(&mut current));
+                    remaining = self.gap;
+                } else {
+                    current.push(p);
+                    remaining = self.dash;
+                }
+                on = !on;
+            }
+            remaining -= len - s;
+            if on {
+                current.push(b);
+            }
+        }
+        if current.len() > 1 {
+            res.push(current);
+        }
+        res
+    }
+}
+
 /// A colored line with a default border radius
 #[derive(Copy, Clone)]
 pub struct Line {
@@ -43,6 +143,10 @@ pub struct Line {
     pub radius: Radius,
     /// The line shape
     pub shape: Shape,
+    /// The shape of the corners of polylines
+    pub join: Join,
+    /// The dash pattern
+    pub dash: Option<Dash>,
 }
 
 impl Line {
@@ -52,6 +156,8 @@ impl Line {
             color,
             radius,
             shape: Shape::Square,
+            join: Join::Miter(4.0),
+            dash: None,
         }
     }
 
@@ -61,6 +167,8 @@ impl Line {
             color,
             radius,
             shape: Shape::Round,
+            join: Join::Round,
+            dash: None,
         }
     }
 
@@ -88,6 +196,24 @@ impl Line {
         self
     }
 
+    /// Sets join.
+    pub fn join(mut self, value: Join) -> Self {
+        self.join = value;
+        self
+    }
+
+    /// Sets dash pattern.
+    pub fn dash(mut self, value: Dash) -> Self {
+        self.dash = Some(value);
+        self
+    }
+
+    /// Sets optional dash pattern.
+    pub fn maybe_dash(mut self, value: Option<Dash>) -> Self {
+        self.dash = value;
+        self
+    }
+
     /// Draws line using default method between points.
     #[inline(always)]
     pub fn draw_from_to<P: Into<types::Vec2d>, G>(
@@ -134,7 +260,31 @@ impl Line {
     ) where
         G: Graphics,
     {
-        let line = line.into();
+        let mut line = line.into();
+        if let Some(dash) = self.dash {
+            let points = [[line[0], line[1]], [line[2], line[3]]];
+            g.tri_list(draw_state, &self.color, |f| {
+                triangulation::with_dashed_polyline_tri_list(
+                    transform,
+                    &points,
+                    false,
+                    self.radius,
+                    dash,
+                    self.join,
+                    self.shape,
+                    |vertices| f(vertices),
+                )
+            });
+            return;
+        }
+        if let Shape::ProjectingSquare = self.shape {
+            let (dx, dy) = (line[2] - line[0], line[3] - line[1]);
+            let len = (dx * dx + dy * dy).sqrt();
+            if len > 0.0 {
+                let (ex, ey) = (dx / len * self.radius, dy / len * self.radius);
+                line = [line[0] - ex, line[1] - ey, line[2] + ex, line[3] + ey];
+            }
+        }
         g.tri_list(draw_state, &self.color, |f| {
             triangulation::with_round_border_line_tri_list(
                 self.shape.resolution(),
@@ -146,6 +296,91 @@ impl Line {
         });
     }
 
+    /// Draws connected line segments through points using default method.
+    ///
+    /// When `closed` is `true`, the last point connects to the first point.
+    #[inline(always)]
+    pub fn draw_polyline<G>(
+        &self,
+        points: types::Polygon<'_>,
+        closed: bool,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        g.polyline(self, points, closed, draw_state, transform);
+    }
+
+    /// Draws connected line segments through points using triangulation.
+    pub fn draw_polyline_tri<G>(
+        &self,
+        points: types::Polygon<'_>,
+        closed: bool,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        g.tri_list(draw_state, &self.color, |f| match self.dash {
+            None => triangulation::with_polyline_tri_list(
+                transform,
+                points,
+                closed,
+                self.radius,
+                self.join,
+                self.shape,
+                |vertices| f(vertices),
+            ),
+            Some(dash) => triangulation::with_dashed_polyline_tri_list(
+                transform,
+                points,
+                closed,
+                self.radius,
+                dash,
+                self.join,
+                self.shape,
+                |vertices| f(vertices),
+            ),
+        });
+    }
+
+    /// Draws the outline of a path.
+    pub fn draw_path<G>(&self, path: &Path, draw_state: &DrawState, transform: Matrix2d, g: &mut G)
+    where
+        G: Graphics,
+    {
+        let sub_paths = path.flatten(path::tolerance(transform));
+        g.tri_list(draw_state, &self.color, |f| {
+            for sub_path in &sub_paths {
+                let (points, closed) = (&sub_path.points, sub_path.closed);
+                match self.dash {
+                    None => triangulation::with_polyline_tri_list(
+                        transform,
+                        points,
+                        closed,
+                        self.radius,
+                        self.join,
+                        self.shape,
+                        |vertices| f(vertices),
+                    ),
+                    Some(dash) => triangulation::with_dashed_polyline_tri_list(
+                        transform,
+                        points,
+                        closed,
+                        self.radius,
+                        dash,
+                        self.join,
+                        self.shape,
+                        |vertices| f(vertices),
+                    ),
+                }
+            }
+        });
+    }
+
     /// Draws an arrow
     ///
     /// Head size is the sides of the triangle
@@ -190,4 +425,106 @@ mod test {
             .shape(Shape::Round)
             .hue_deg(1.0);
     }
+
+    #[test]
+    fn test_polyline() {
+        use crate::{software::Canvas, Context};
+
+        let c = Context::new_abs(20.0, 20.0);
+        let points = [[2.0, 10.0], [10.0, 10.0], [10.0, 2.0]];
+        for &(join, corner) in &[
+            (Join::Miter(4.0), [255; 4]),
+            (Join::Miter(1.0), [0; 4]),
+            (Join::Bevel, [0; 4]),
+            (Join::Round, [0; 4]),
+        ] {
+            let mut canvas = Canvas::new(20, 20);
+            Line::new([1.0; 4], 2.0).join(join).draw_polyline(
+                &points,
+                false,
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+            assert_eq!(canvas.pixel(11, 11), corner);
+            assert_eq!(canvas.pixel(10, 10), [255; 4]);
+            assert_eq!(canvas.pixel(1, 10), [0; 4]);
+        }
+
+        let mut canvas = Canvas::new(20, 20);
+        Line::new([1.0; 4], 2.0)
+            .shape(Shape::ProjectingSquare)
+            .draw_polyline(&points, false, &c.draw_state, c.transform, &mut canvas);
+        assert_eq!(canvas.pixel(1, 10), [255; 4]);
+        assert_eq!(canvas.pixel(10, 1), [255; 4]);
+    }
+
+    #[test]
+    fn test_polyline_overlap() {
+        use crate::{software::Canvas, Context};
+
+        // Overlapping triangles would blend twice.
+        let c = Context::new_abs(40.0, 40.0);
+        let points = [
+            [5.0, 5.0],
+            [30.0, 8.0],
+            [12.0, 20.0],
+            [35.0, 35.0],
+            [5.0, 30.0],
+        ];
+        for &closed in &[false, true] {
+            for &join in &[Join::Miter(10.0), Join::Round, Join::Bevel] {
+                let mut canvas = Canvas::new(40, 40);
+                Line::new_round([1.0, 1.0, 1.0, 0.5], 2.5)
+                    .join(join)
+                    .draw_polyline(&points, closed, &c.draw_state, c.transform, &mut canvas);
+                let alpha: Vec<u8> = canvas.pixels().chunks(4).map(|p| p[3]).collect();
+                let covered = alpha[5 * 40 + 5];
+                assert!(covered > 0);
+                assert!(alpha.iter().all(|&a| a == 0 || a == covered));
+            }
+        }
+    }
+
+    #[test]
+    fn test_dash_split() {
+        let points = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]];
+        let dashes = Dash::new(4.0, 2.0).split(&points, false);
+        assert_eq!(
+            dashes,
+            vec![
+                vec![[0.0, 0.0], [4.0, 0.0]],
+                vec![[6.0, 0.0], [10.0, 0.0]],
+                vec![[10.0, 2.0], [10.0, 6.0]],
+                vec![[10.0, 8.0], [10.0, 10.0]],
+            ]
+        );
+        // Dash continues around the corner.
+        let dashes = Dash::new(4.0, 2.0).offset(3.0).split(&points, false);
+        assert_eq!(dashes[2], vec![[9.0, 0.0], [10.0, 0.0], [10.0, 3.0]]);
+        // Dots.
+        let dots = Dash::new(0.0, 5.0).split(&points, true);
+        assert_eq!(dots.len(), 7);
+        assert_eq!(dots[0], vec![[0.0, 0.0], [0.0, 0.0]]);
+        assert_eq!(dots[1], vec![[5.0, 0.0], [5.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_dotted() {
+        use crate::{software::Canvas, Context};
+
+        let c = Context::new_abs(20.0, 5.0);
+        let mut canvas = Canvas::new(20, 5);
+        Line::new_round([1.0; 4], 1.5)
+            .dash(Dash::new(0.0, 10.0))
+            .draw(
+                [5.0, 2.5, 20.0, 2.5],
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+        assert_eq!(canvas.pixel(5, 2), [255; 4]);
+        assert_eq!(canvas.pixel(10, 2), [0; 4]);
+        assert_eq!(canvas.pixel(15, 2), [255; 4]);
+    }
 }
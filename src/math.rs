@@ -426,6 +426,34 @@ where
     res
 }
 
+/// Returns true if polygon is convex.
+///
+/// This is done by checking that all corners turn in the same direction.
+/// Corners where the edges are parallel are ignored.
+#[allow(clippy::just_underscores_and_digits)] // Naming convention.
+pub fn is_convex<T>(polygon: Polygon<'_, T>) -> bool
+where
+    T: Float,
+{
+    use vecmath::traits::Zero;
+
+    let _0: T = Zero::zero();
+    let n = polygon.len();
+    let (mut positive, mut negative) = (false, false);
+    for i in 0..n {
+        let a = polygon[previous(n, i)];
+        let b = polygon[i];
+        let c = polygon[(i + 1) % n];
+        let side = line_side([a[0], a[1], b[0], b[1]], c);
+        positive |= side > _0;
+        negative |= side < _0;
+        if positive && negative {
+            return false;
+        }
+    }
+    true
+}
+
 /// Returns a number that tells which side it is relative to a line.
 ///
 /// Computes the cross product of the vector that gives the line
@@ -507,6 +535,7 @@ mod test_triangle {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_triangle() {
         // Triangle counter clock-wise.
         let tri_1 = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
@@ -518,6 +547,14 @@ mod test_triangle {
         assert_eq!(triangle_face(tri_1), false);
         assert!(triangle_face(tri_2));
     }
+
+    #[test]
+    fn test_is_convex() {
+        let square = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let arrow = [[0.0, 0.0], [1.0, 0.5], [0.0, 1.0], [0.5, 0.5]];
+        assert!(is_convex(&square));
+        assert!(!is_convex(&arrow));
+    }
 }
 
 /// Transforms from cartesian coordinates to barycentric.
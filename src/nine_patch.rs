@@ -0,0 +1,310 @@
+//! Draw nine-patch images
+//!
+//! A nine-patch image is split into 3x3 parts by border insets.
+//! The corners keep their size, the edges stretch or tile along one axis,
+//! and the center stretches or tiles along both axes.
+//! This is useful for skinning buttons and panels of different sizes.
+//!
+//! ```
+//! use graphics::{software::{Canvas, Texture}, Context, NinePatch};
+//!
+//! let texture = Texture::from_rgba8(3, 3, vec![255; 36]).unwrap();
+//! let c = Context::new_abs(100.0, 40.0);
+//! let mut canvas = Canvas::new(100, 40);
+//! NinePatch::new([1.0; 4]).draw(
+//!     &texture,
+//!     [10.0, 10.0, 80.0, 20.0],
+//!     &c.draw_state,
+//!     c.transform,
+//!     &mut canvas,
+//! );
+//! ```
+
+use crate::{
+    math::{Matrix2d, Scalar},
+    triangulation,
+    types::{Color, Rectangle, SourceRectangle},
+    DrawState, Graphics, ImageSize, BACK_END_MAX_VERTEX_COUNT as BUFFER_SIZE,
+};
+
+/// How the edges and center of a nine-patch image fill their area.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Fill {
+    /// Scales the part to fit.
+    Stretch,
+    /// Repeats the part at its source size, cutting the last repetition.
+    Tile,
+}
+
+/// An image split into 3x3 parts, where the corners are not scaled
+#[derive(Copy, Clone)]
+pub struct NinePatch {
+    /// The color
+    pub color: Option<Color>,
+    /// The image source rectangle
+    pub source_rectangle: Option<SourceRectangle>,
+    /// The border insets in source pixels: [left, top, right, bottom]
+    pub insets: [Scalar; 4],
+    /// How the edges fill their area
+    pub edges: Fill,
+    /// How the center fills its area
+    pub center: Fill,
+}
+
+impl NinePatch {
+    /// Creates a new nine-patch image with border insets in source pixels.
+    ///
+    /// The insets are [left, top, right, bottom].
+    pub fn new(insets: [Scalar; 4]) -> NinePatch {
+        NinePatch {
+            color: None,
+            source_rectangle: None,
+            insets,
+            edges: Fill::Stretch,
+            center: Fill::Stretch,
+        }
+    }
+
+    /// Sets color.
+    pub fn color(mut self, value: Color) -> Self {
+        self.color = Some(value);
+        self
+    }
+
+    /// Sets optional color.
+    pub fn maybe_color(mut self, value: Option<Color>) -> Self {
+        self.color = value;
+        self
+    }
+
+    /// Sets source rectangle.
+    pub fn src_rect(mut self, value: SourceRectangle) -> Self {
+        self.source_rectangle = Some(value);
+        self
+    }
+
+    /// Sets optional source rectangle.
+    pub fn maybe_src_rect(mut self, value: Option<SourceRectangle>) -> Self {
+        self.source_rectangle = value;
+        self
+    }
+
+    /// Sets how the edges fill their area.
+    pub fn edges(mut self, value: Fill) -> Self {
+        self.edges = value;
+        self
+    }
+
+    /// Sets how the center fills its area.
+    pub fn center(mut self, value: Fill) -> Self {
+        self.center = value;
+        self
+    }
+
+    /// Draws the nine-patch image inside a rectangle.
+    ///
+    /// When the rectangle is smaller than the insets,
+    /// the corners are scaled down to fit.
+    /// Likewise, insets larger than the source rectangle are scaled down to fit.
+    pub fn draw<R: Into<Rectangle>, G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        rectangle: R,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let rectangle = rectangle.into();
+        let color = self.color.unwrap_or([1.0; 4]);
+        let src = self.source_rectangle.unwrap_or({
+            let (w, h) = texture.get_size();
+            [0.0, 0.0, w as Scalar, h as Scalar]
+        });
+        let [left, top, right, bottom] = self.insets;
+        let xs = slices(rectangle[0], rectangle[2], src[0], src[2], left, right);
+        let ys = slices(rectangle[1], rectangle[3], src[1], src[3], top, bottom);
+        g.tri_list_uv(draw_state, &color, texture, |f| {
+            let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            let mut uvs: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            let mut i = 0;
+            let mut quad = |rect: Rectangle, src: SourceRectangle| {
+                if i + 6 > BUFFER_SIZE {
+                    f(&vertices[0..i], &uvs[0..i]);
+                    i = 0;
+                }
+                vertices[i..i + 6]
+                    .copy_from_slice(&triangulation::rect_tri_list_xy(transform, rect));
+                uvs[i..i + 6].copy_from_slice(&triangulation::rect_tri_list_uv(texture, src));
+                i += 6;
+            };
+            for (row, &y_slice) in ys.iter().enumerate() {
+                for (col, &x_slice) in xs.iter().enumerate() {
+                    let fill = match (row == 1, col == 1) {
+                        (true, true) => self.center,
+                        (false, false) => Fill::Stretch,
+                        _ => self.edges,
+                    };
+                    let tile_x = fill == Fill::Tile && col == 1;
+                    let tile_y = fill == Fill::Tile && row == 1;
+                    for (x, sx) in tiles(x_slice, tile_x) {
+                        for (y, sy) in tiles(y_slice, tile_y) {
+                            quad([x.0, y.0, x.1, y.1], [sx.0, sy.0, sx.1, sy.1]);
+                        }
+                    }
+                }
+            }
+            if i > 0 {
+                f(&vertices[0..i], &uvs[0..i]);
+            }
+        });
+    }
+}
+
+/// A part along one axis: (position, size) and (source position, source size).
+type Slice = ((Scalar, Scalar), (Scalar, Scalar));
+
+/// Splits one axis into three parts.
+fn slices(
+    pos: Scalar,
+    size: Scalar,
+    src_pos: Scalar,
+    src_size: Scalar,
+    start: Scalar,
+    end: Scalar,
+) -> [Slice; 3] {
+    let (start, end) = (start.max(0.0), end.max(0.0));
+    // Scale the source insets down when they do not fit in the source.
+    let src_scale = if start + end > src_size && start + end > 0.0 {
+        src_size.max(0.0) / (start + end)
+    } else {
+        1.0
+    };
+    let (start, end) = (start * src_scale, end * src_scale);
+    // Scale the corners down when they do not fit.
+    let scale = if start + end > size && start + end > 0.0 {
+        size / (start + end)
+    } else {
+        1.0
+    };
+    let (a, b) = (start * scale, end * scale);
+    [
+        ((pos, a), (src_pos, start)),
+        (
+            (pos + a, size - a - b),
+            (src_pos + start, src_size - start - end),
+        ),
+        ((pos + size - b, b), (src_pos + src_size - end, end)),
+    ]
+}
+
+/// Returns the repetitions of a part along one axis.
+///
+/// When tiled, each repetition has the source size,
+/// and the last one is cut to fit.
+fn tiles(slice: Slice, tile: bool) -> Vec<Slice> {
+    let ((pos, size), (src_pos, src_size)) = slice;
+    if size <= 0.0 {
+        return vec![];
+    }
+    if !tile || src_size <= 0.0 {
+        return vec![slice];
+    }
+    let n = (size / src_size).ceil() as usize;
+    (0..n)
+        .map(|k| {
+            let offset = k as Scalar * src_size;
+            let part = src_size.min(size - offset);
+            ((pos + offset, part), (src_pos, part))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        software::{Canvas, Texture},
+        Context,
+    };
+
+    /// A 12x12 texture with red corners, green edges
+    /// and a center with a white left half and a black right half.
+    fn texture() -> Texture {
+        let mut pixels = vec![];
+        for y in 0..12 {
+            for x in 0..12 {
+                let corner = !(4..8).contains(&x) && !(4..8).contains(&y);
+                let center = (4..8).contains(&x) && (4..8).contains(&y);
+                pixels.extend_from_slice(&match (corner, center) {
+                    (true, _) => [255, 0, 0, 255],
+                    (_, true) if x < 6 => [255, 255, 255, 255],
+                    (_, true) => [0, 0, 0, 255],
+                    _ => [0, 255, 0, 255],
+                });
+            }
+        }
+        Texture::from_rgba8(12, 12, pixels).unwrap()
+    }
+
+    #[test]
+    fn test_nine_patch() {
+        let texture = texture();
+        let c = Context::new_abs(20.0, 20.0);
+        let mut canvas = Canvas::new(20, 20);
+        let nine_patch = NinePatch::new([4.0; 4]);
+        nine_patch.draw(
+            &texture,
+            [0.0, 0.0, 20.0, 20.0],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        let red = [255, 0, 0, 255];
+        let green = [0, 255, 0, 255];
+        // Corners keep their size.
+        assert_eq!(canvas.pixel(3, 3), red);
+        assert_eq!(canvas.pixel(16, 16), red);
+        assert_eq!(canvas.pixel(4, 1), green);
+        assert_eq!(canvas.pixel(15, 18), green);
+        // The stretched center is white in the left half.
+        assert_eq!(canvas.pixel(7, 10), [255; 4]);
+        assert_eq!(canvas.pixel(12, 10), [0, 0, 0, 255]);
+
+        let mut canvas = Canvas::new(20, 20);
+        nine_patch.center(Fill::Tile).draw(
+            &texture,
+            [0.0, 0.0, 20.0, 20.0],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        // Tiles repeat every 4 pixels from x = 4.
+        assert_eq!(canvas.pixel(5, 10), [255; 4]);
+        assert_eq!(canvas.pixel(7, 10), [0, 0, 0, 255]);
+        assert_eq!(canvas.pixel(9, 10), [255; 4]);
+        assert_eq!(canvas.pixel(15, 10), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_small() {
+        // Corners are scaled down when the rectangle is too small.
+        let xs = slices(0.0, 4.0, 0.0, 12.0, 4.0, 4.0);
+        assert_eq!(xs[0], ((0.0, 2.0), (0.0, 4.0)));
+        assert_eq!(xs[1], ((2.0, 0.0), (4.0, 4.0)));
+        assert_eq!(xs[2], ((2.0, 2.0), (8.0, 4.0)));
+        assert!(tiles(xs[1], true).is_empty());
+        assert_eq!(tiles(((0.0, 10.0), (4.0, 4.0)), true).len(), 3);
+    }
+
+    #[test]
+    fn test_large_insets() {
+        // Insets are scaled down when the source is too small.
+        let xs = slices(0.0, 20.0, 2.0, 12.0, 12.0, 4.0);
+        assert_eq!(xs[0], ((0.0, 9.0), (2.0, 9.0)));
+        assert_eq!(xs[1], ((9.0, 8.0), (11.0, 0.0)));
+        assert_eq!(xs[2], ((17.0, 3.0), (11.0, 3.0)));
+        assert_eq!(tiles(xs[1], true), vec![xs[1]]);
+    }
+}
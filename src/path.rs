@@ -0,0 +1,317 @@
+//! Vector paths with lines, Bézier curves and arcs.
+//!
+//! A `Path` is built from segments, like in SVG or HTML canvas.
+//! It is filled with `Polygon::draw_path` and stroked with `Line::draw_path`.
+//!
+//! Curves are flattened into line segments when drawn.
+//! The number of segments adapts to the scale of the transform,
+//! so zooming in keeps curves smooth.
+//!
+//! ```
+//! use graphics::{path::Path, software::Canvas, Context, Line, Polygon};
+//!
+//! let c = Context::new_abs(100.0, 100.0);
+//! let path = Path::new()
+//!     .move_to([10.0, 50.0])
+//!     .quad_to([50.0, 0.0], [90.0, 50.0])
+//!     .cubic_to([70.0, 100.0], [30.0, 100.0], [10.0, 50.0])
+//!     .close();
+//! let mut canvas = Canvas::new(100, 100);
+//! Polygon::new([1.0; 4]).draw_path(&path, &c.draw_state, c.transform, &mut canvas);
+//! Line::new([0.0, 0.0, 0.0, 1.0], 1.0).draw_path(&path, &c.draw_state, c.transform, &mut canvas);
+//! ```
+
+use crate::{
+    math::{add, get_scale, mul_scalar, square_len, sub, Matrix2d, Scalar, Vec2d},
+    radians::Radians,
+};
+
+/// The default maximum distance between a curve and its flattened segments,
+/// in normalized device coordinates.
+///
+/// This is a quarter of a pixel when the viewport is 1000 pixels wide.
+pub const DEFAULT_TOLERANCE: Scalar = 0.0005;
+
+/// A segment of a path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Segment {
+    /// Starts a new sub-path at a point.
+    MoveTo(Vec2d),
+    /// A straight line to a point.
+    LineTo(Vec2d),
+    /// A quadratic Bézier curve with a control point.
+    QuadTo {
+        /// The control point.
+        control: Vec2d,
+        /// The end point.
+        to: Vec2d,
+    },
+    /// A cubic Bézier curve with two control points.
+    CubicTo {
+        /// The first control point.
+        control1: Vec2d,
+        /// The second control point.
+        control2: Vec2d,
+        /// The end point.
+        to: Vec2d,
+    },
+    /// A circular arc tangent to the lines from current point to corner
+    /// and from corner to `to`.
+    ArcTo {
+        /// The corner point.
+        corner: Vec2d,
+        /// The point giving the direction of the second tangent.
+        to: Vec2d,
+        /// The radius of the arc.
+        radius: Scalar,
+    },
+    /// Closes the current sub-path with a line to its start point.
+    Close,
+}
+
+/// A flattened sub-path.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubPath {
+    /// The points.
+    pub points: Vec<Vec2d>,
+    /// Whether the last point connects to the first point.
+    pub closed: bool,
+}
+
+/// A vector path built from lines, curves and arcs.
+///
+/// Paths start at the origin unless `move_to` is called first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    /// The segments, in order.
+    pub segments: Vec<Segment>,
+}
+
+impl Path {
+    /// Creates a new empty path.
+    pub fn new() -> Path {
+        Path { segments: vec![] }
+    }
+
+    /// Starts a new sub-path at a point.
+    pub fn move_to(mut self, p: Vec2d) -> Self {
+        self.segments.push(Segment::MoveTo(p));
+        self
+    }
+
+    /// Adds a straight line to a point.
+    pub fn line_to(mut self, p: Vec2d) -> Self {
+        self.segments.push(Segment::LineTo(p));
+        self
+    }
+
+    /// Adds a quadratic Bézier curve.
+    pub fn quad_to(mut self, control: Vec2d, to: Vec2d) -> Self {
+        self.segments.push(Segment::QuadTo { control, to });
+        self
+    }
+
+    /// Adds a cubic Bézier curve.
+    pub fn cubic_to(mut self, control1: Vec2d, control2: Vec2d, to: Vec2d) -> Self {
+        self.segments.push(Segment::CubicTo {
+            control1,
+            control2,
+            to,
+        });
+        self
+    }
+
+    /// Adds a circular arc that rounds the corner at `corner`.
+    ///
+    /// Works like `arcTo` in HTML canvas:
+    /// A line is added from the current point to the start of the arc,
+    /// and the path ends at the point where the arc touches
+    /// the line from `corner` to `to`.
+    pub fn arc_to(mut self, corner: Vec2d, to: Vec2d, radius: Scalar) -> Self {
+        self.segments.push(Segment::ArcTo { corner, to, radius });
+        self
+    }
+
+    /// Closes the current sub-path.
+    pub fn close(mut self) -> Self {
+        self.segments.push(Segment::Close);
+        self
+    }
+
+    /// Flattens the path into sub-paths of straight lines.
+    ///
+    /// The tolerance is the maximum distance between curves and lines.
+    /// Sub-paths with less than 2 points are skipped.
+    pub fn flatten(&self, tolerance: Scalar) -> Vec<SubPath> {
+        let mut res = vec![];
+        let mut points: Vec<Vec2d> = vec![[0.0, 0.0]];
+        let mut finish = |points: &mut Vec<Vec2d>, closed: bool| {
+            let start = points[0];
+            let current = points[points.len() - 1];
+            if points.len() >= 2 {
+                res.push(SubPath {
+                    points: std::mem::take(points),
+                    closed,
+                });
+            }
+            *points = vec![if closed { start } else { current }];
+        };
+        for segment in &self.segments {
+            let p0 = points[points.len() - 1];
+            match *segment {
+                Segment::MoveTo(p) => {
+                    finish(&mut points, false);
+                    points[0] = p;
+                }
+                Segment::LineTo(p) => points.push(p),
+                Segment::QuadTo { control, to } => {
+                    let d = sub(add(p0, to), mul_scalar(control, 2.0));
+                    let n = ((len(d) / (4.0 * tolerance)).sqrt().ceil() as usize).max(1);
+                    points.extend((1..=n).map(|i| {
+                        let t = i as Scalar / n as Scalar;
+                        let u = 1.0 - t;
+                        add(
+                            add(mul_scalar(p0, u * u), mul_scalar(control, 2.0 * u * t)),
+                            mul_scalar(to, t * t),
+                        )
+                    }));
+                }
+                Segment::CubicTo {
+                    control1: c1,
+                    control2: c2,
+                    to,
+                } => {
+                    let d1 = sub(add(p0, c2), mul_scalar(c1, 2.0));
+                    let d2 = sub(add(c1, to), mul_scalar(c2, 2.0));
+                    let d = len(d1).max(len(d2));
+                    let n = ((0.75 * d / tolerance).sqrt().ceil() as usize).max(1);
+                    points.extend((1..=n).map(|i| {
+                        let t = i as Scalar / n as Scalar;
+                        let u = 1.0 - t;
+                        add(
+                            add(mul_scalar(p0, u * u * u), mul_scalar(c1, 3.0 * u * u * t)),
+                            add(mul_scalar(c2, 3.0 * u * t * t), mul_scalar(to, t * t * t)),
+                        )
+                    }));
+                }
+                Segment::ArcTo { corner, to, radius } => {
+                    arc_to(&mut points, p0, corner, to, radius, tolerance)
+                }
+                Segment::Close => finish(&mut points, true),
+            }
+        }
+        finish(&mut points, false);
+        res
+    }
+}
+
+/// Returns the flattening tolerance in coordinates before transform.
+pub fn tolerance(transform: Matrix2d) -> Scalar {
+    let s = get_scale(transform);
+    DEFAULT_TOLERANCE / s[0].max(s[1])
+}
+
+fn len(a: Vec2d) -> Scalar {
+    square_len(a).sqrt()
+}
+
+/// Adds points of an arc tangent to the lines `p0-p1` and `p1-p2`.
+fn arc_to(
+    points: &mut Vec<Vec2d>,
+    p0: Vec2d,
+    p1: Vec2d,
+    p2: Vec2d,
+    radius: Scalar,
+    tolerance: Scalar,
+) {
+    let (v1, v2) = (sub(p0, p1), sub(p2, p1));
+    let (l1, l2) = (len(v1), len(v2));
+    let cross = v1[0] * v2[1] - v1[1] * v2[0];
+    // Use a straight line when the points are on a line.
+    if radius <= 0.0 || l1 == 0.0 || l2 == 0.0 || cross.abs() <= 1e-9 * l1 * l2 {
+        points.push(p1);
+        return;
+    }
+    let (v1, v2) = (mul_scalar(v1, 1.0 / l1), mul_scalar(v2, 1.0 / l2));
+    // Half the angle between the tangent lines.
+    let half = 0.5 * (v1[0] * v2[0] + v1[1] * v2[1]).clamp(-1.0, 1.0).acos();
+    let d = radius / half.tan();
+    let bisector = add(v1, v2);
+    let center = add(
+        p1,
+        mul_scalar(bisector, radius / half.sin() / len(bisector)),
+    );
+    let t1 = add(p1, mul_scalar(v1, d));
+    let t2 = add(p1, mul_scalar(v2, d));
+    let a1 = (t1[1] - center[1]).atan2(t1[0] - center[0]);
+    let a2 = (t2[1] - center[1]).atan2(t2[0] - center[0]);
+    let mut sweep = a2 - a1;
+    if sweep > <Scalar as Radians>::_180() {
+        sweep -= <Scalar as Radians>::_360();
+    } else if sweep < -<Scalar as Radians>::_180() {
+        sweep += <Scalar as Radians>::_360();
+    }
+    // Largest angle step where the arc stays within tolerance.
+    let step = 2.0 * (1.0 - (tolerance / radius).min(1.0)).acos();
+    let n = ((sweep.abs() / step).ceil() as usize).max(1);
+    points.push(t1);
+    points.extend((1..=n).map(|i| {
+        let angle = a1 + sweep * i as Scalar / n as Scalar;
+        [
+            center[0] + radius * angle.cos(),
+            center[1] + radius * angle.sin(),
+        ]
+    }));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_flatten() {
+        let path = Path::new()
+            .move_to([0.0, 0.0])
+            .quad_to([50.0, 100.0], [100.0, 0.0])
+            .close()
+            .line_to([0.0, 10.0]);
+        let sub_paths = path.flatten(0.1);
+        assert_eq!(sub_paths.len(), 2);
+        assert!(sub_paths[0].closed);
+        assert!(!sub_paths[1].closed);
+        // The path continues from the start of the closed sub-path.
+        assert_eq!(sub_paths[1].points, vec![[0.0, 0.0], [0.0, 10.0]]);
+
+        let points = &sub_paths[0].points;
+        assert_eq!(points[points.len() - 1], [100.0, 0.0]);
+        // The top of the curve is at y = 50.
+        let top = points.iter().map(|p| p[1]).fold(0.0, Scalar::max);
+        assert!(top > 49.9 && top <= 50.0);
+        // Smaller tolerance gives more points.
+        assert!(path.flatten(0.01)[0].points.len() > points.len());
+    }
+
+    #[test]
+    fn test_arc_to() {
+        let path = Path::new()
+            .move_to([0.0, 0.0])
+            .arc_to([10.0, 0.0], [10.0, 10.0], 5.0);
+        let points = &path.flatten(0.01)[0].points;
+        assert!((points[1][0] - 5.0).abs() < 1e-9 && points[1][1].abs() < 1e-9);
+        let last = points[points.len() - 1];
+        assert!((last[0] - 10.0).abs() < 1e-9 && (last[1] - 5.0).abs() < 1e-9);
+        for p in &points[1..] {
+            let r = len(sub(*p, [5.0, 5.0]));
+            assert!((r - 5.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_tolerance() {
+        use crate::{Context, Transformed};
+
+        let c = Context::new_abs(1000.0, 1000.0);
+        assert!((tolerance(c.transform) - 0.25).abs() < 1e-9);
+        assert!((tolerance(c.transform.zoom(2.0)) - 0.125).abs() < 1e-9);
+    }
+}
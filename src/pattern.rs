@@ -0,0 +1,317 @@
+//! Repeating image patterns.
+//!
+//! A `Pattern` fills shapes with a texture repeated in tiles.
+//! Shapes are drawn with a pattern using e.g. `Rectangle::draw_pattern`.
+//! The tiles are in the same coordinates as the shape, before transform.
+//!
+//! Back-ends are not assumed to support wrapping texture coordinates,
+//! so the triangles of the shape are split at tile boundaries.
+//! The result is sent to the back-end using `Graphics::tri_list_uv`.
+//!
+//! ```
+//! use graphics::{pattern::Pattern, software::{Canvas, Texture}, Context, Rectangle};
+//!
+//! let texture = Texture::from_rgba8(2, 2, vec![255; 16]).unwrap();
+//! let c = Context::new_abs(100.0, 100.0);
+//! let pattern = Pattern::new([16.0, 16.0]).offset([8.0, 8.0]);
+//! let mut canvas = Canvas::new(100, 100);
+//! Rectangle::new([1.0; 4]).draw_pattern(
+//!     &pattern,
+//!     &texture,
+//!     [0.0, 0.0, 100.0, 100.0],
+//!     &c.draw_state,
+//!     c.transform,
+//!     &mut canvas,
+//! );
+//! ```
+
+use crate::{
+    math::{Matrix2d, Scalar, Vec2d},
+    triangulation::{tx, ty},
+    types::{Color, SourceRectangle},
+    DrawState, Graphics, ImageSize, BACK_END_MAX_VERTEX_COUNT as BUFFER_SIZE,
+};
+
+/// A texture repeated in tiles.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pattern {
+    /// The color multiplied with the texture
+    pub color: Option<Color>,
+    /// The image source rectangle of one tile
+    pub source_rectangle: Option<SourceRectangle>,
+    /// The size of one tile
+    pub size: Vec2d,
+    /// The position of the upper left corner of a tile
+    pub offset: Vec2d,
+    /// The rotation of the tiles around the offset, in radians
+    pub rotation: Scalar,
+}
+
+impl Pattern {
+    /// Creates a new pattern with tile size.
+    pub fn new(size: Vec2d) -> Pattern {
+        Pattern {
+            color: None,
+            source_rectangle: None,
+            size,
+            offset: [0.0; 2],
+            rotation: 0.0,
+        }
+    }
+
+    /// Sets color.
+    pub fn color(mut self, value: Color) -> Self {
+        self.color = Some(value);
+        self
+    }
+
+    /// Sets optional color.
+    pub fn maybe_color(mut self, value: Option<Color>) -> Self {
+        self.color = value;
+        self
+    }
+
+    /// Sets source rectangle.
+    pub fn src_rect(mut self, value: SourceRectangle) -> Self {
+        self.source_rectangle = Some(value);
+        self
+    }
+
+    /// Sets optional source rectangle.
+    pub fn maybe_src_rect(mut self, value: Option<SourceRectangle>) -> Self {
+        self.source_rectangle = value;
+        self
+    }
+
+    /// Sets the position of the upper left corner of a tile.
+    pub fn offset(mut self, value: Vec2d) -> Self {
+        self.offset = value;
+        self
+    }
+
+    /// Sets the rotation of the tiles in radians.
+    pub fn rotation(mut self, value: Scalar) -> Self {
+        self.rotation = value;
+        self
+    }
+
+    /// Converts a position to tile coordinates,
+    /// where each tile is a unit square.
+    fn tile_coords(&self, pos: Vec2d) -> Vec2d {
+        let (s, c) = self.rotation.sin_cos();
+        let (x, y) = (pos[0] - self.offset[0], pos[1] - self.offset[1]);
+        [
+            (x * c + y * s) / self.size[0],
+            (y * c - x * s) / self.size[1],
+        ]
+    }
+
+    /// Converts tile coordinates back to a position.
+    fn position(&self, tile: Vec2d) -> Vec2d {
+        let (s, c) = self.rotation.sin_cos();
+        let (x, y) = (tile[0] * self.size[0], tile[1] * self.size[1]);
+        [
+            self.offset[0] + x * c - y * s,
+            self.offset[1] + x * s + y * c,
+        ]
+    }
+
+    /// Draws triangles filled with the pattern.
+    ///
+    /// `triangles` streams the triangles in coordinates before transform,
+    /// in the same way as the closures passed to `Graphics::tri_list`.
+    /// Nothing is drawn when the tile size or the texture is empty.
+    pub fn draw_tri_list<F, G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+        mut triangles: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+        G: Graphics,
+    {
+        let (w, h) = texture.get_size();
+        if self.size[0] <= 0.0 || self.size[1] <= 0.0 || w == 0 || h == 0 {
+            return;
+        }
+        let color = self.color.unwrap_or([1.0; 4]);
+        let (w, h) = (w as Scalar, h as Scalar);
+        let src = self.source_rectangle.unwrap_or([0.0, 0.0, w, h]);
+        g.tri_list_uv(draw_state, &color, texture, |f| {
+            let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            let mut uvs: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            let mut i = 0;
+            let mut emit = |tile: Vec2d, cell: [Scalar; 2]| {
+                let p = self.position(tile);
+                vertices[i] = [tx(transform, p[0], p[1]), ty(transform, p[0], p[1])];
+                uvs[i] = [
+                    ((src[0] + (tile[0] - cell[0]) * src[2]) / w) as f32,
+                    ((src[1] + (tile[1] - cell[1]) * src[3]) / h) as f32,
+                ];
+                i += 1;
+                // The buffer size is a multiple of 3.
+                if i == BUFFER_SIZE {
+                    f(&vertices, &uvs);
+                    i = 0;
+                }
+            };
+            triangles(&mut |tris| {
+                for tri in tris.chunks_exact(3) {
+                    let p = |k: usize| [tri[k][0] as Scalar, tri[k][1] as Scalar];
+                    let tri = [
+                        self.tile_coords(p(0)),
+                        self.tile_coords(p(1)),
+                        self.tile_coords(p(2)),
+                    ];
+                    split(tri, &mut emit);
+                }
+            });
+            if i > 0 {
+                f(&vertices[0..i], &uvs[0..i]);
+            }
+        });
+    }
+}
+
+/// Splits a triangle in tile coordinates at tile boundaries.
+///
+/// Emits the vertices of each piece with the cell it belongs to.
+fn split(tri: [Vec2d; 3], emit: &mut dyn FnMut(Vec2d, [Scalar; 2])) {
+    let range = |k: usize| {
+        let lo = tri[0][k].min(tri[1][k]).min(tri[2][k]);
+        let hi = tri[0][k].max(tri[1][k]).max(tri[2][k]);
+        (lo.floor() as i64, hi.ceil() as i64)
+    };
+    let ((x0, x1), (y0, y1)) = (range(0), range(1));
+    for x in x0..x1 {
+        let x = x as Scalar;
+        let column = clip(&clip(&tri, 0, x, 1.0), 0, x + 1.0, -1.0);
+        if column.len() < 3 {
+            continue;
+        }
+        for y in y0..y1 {
+            let y = y as Scalar;
+            let cell = clip(&clip(&column, 1, y, 1.0), 1, y + 1.0, -1.0);
+            for i in 2..cell.len() {
+                for &p in &[cell[0], cell[i - 1], cell[i]] {
+                    emit(p, [x, y]);
+                }
+            }
+        }
+    }
+}
+
+/// Clips a convex polygon to the side of a line where
+/// `sign * (p[axis] - value) >= 0`.
+fn clip(polygon: &[Vec2d], axis: usize, value: Scalar, sign: Scalar) -> Vec<Vec2d> {
+    let mut res = vec![];
+    let n = polygon.len();
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        let (da, db) = (sign * (a[axis] - value), sign * (b[axis] - value));
+        if da >= 0.0 {
+            res.push(a);
+        }
+        if (da < 0.0 && db > 0.0) || (da > 0.0 && db < 0.0) {
+            let t = da / (da - db);
+            res.push([a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        display_list::DisplayList,
+        radians::Radians,
+        software::{Canvas, Texture},
+        Context, Polygon, Rectangle,
+    };
+
+    /// A 2x2 texture with a white left column and a black right column.
+    fn texture() -> Texture {
+        let (white, black) = ([255; 4], [0, 0, 0, 255]);
+        let mut pixels = vec![];
+        for p in &[white, black, white, black] {
+            pixels.extend_from_slice(p);
+        }
+        Texture::from_rgba8(2, 2, pixels).unwrap()
+    }
+
+    #[test]
+    fn test_pattern() {
+        let texture = texture();
+        let c = Context::new_abs(40.0, 20.0);
+        let white = [255; 4];
+        let black = [0, 0, 0, 255];
+        let draw = |pattern: Pattern| {
+            let mut canvas = Canvas::new(40, 20);
+            Rectangle::new([1.0; 4]).draw_pattern(
+                &pattern,
+                &texture,
+                [0.0, 0.0, 40.0, 20.0],
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+            canvas
+        };
+        let pattern = Pattern::new([10.0, 10.0]);
+        let canvas = draw(pattern);
+        assert_eq!(canvas.pixel(2, 5), white);
+        assert_eq!(canvas.pixel(7, 5), black);
+        assert_eq!(canvas.pixel(32, 15), white);
+        assert_eq!(canvas.pixel(37, 15), black);
+
+        let canvas = draw(pattern.offset([5.0, 0.0]));
+        assert_eq!(canvas.pixel(2, 5), black);
+        assert_eq!(canvas.pixel(7, 5), white);
+
+        // The columns of the texture become rows.
+        let canvas = draw(pattern.rotation(<Scalar as Radians>::_90()));
+        assert_eq!(canvas.pixel(2, 2), white);
+        assert_eq!(canvas.pixel(2, 7), black);
+        assert_eq!(canvas.pixel(30, 12), white);
+        assert_eq!(canvas.pixel(30, 17), black);
+    }
+
+    #[test]
+    fn test_polygon_pattern() {
+        let texture = texture();
+        let c = Context::new_abs(20.0, 20.0);
+        let mut canvas = Canvas::new(20, 20);
+        Polygon::new([1.0; 4]).draw_pattern(
+            &Pattern::new([4.0, 4.0]),
+            &texture,
+            &[[0.0, 0.0], [20.0, 0.0], [0.0, 20.0]],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(1, 1), [255; 4]);
+        assert_eq!(canvas.pixel(3, 1), [0, 0, 0, 255]);
+        assert_eq!(canvas.pixel(5, 9), [255; 4]);
+        assert_eq!(canvas.pixel(15, 15), [0; 4]);
+    }
+
+    #[test]
+    fn test_empty_texture() {
+        // Nothing is drawn instead of dividing by zero.
+        let texture = Texture::from_rgba8(0, 0, vec![]).unwrap();
+        let c = Context::new_abs(20.0, 20.0);
+        let mut list = DisplayList::new();
+        Rectangle::new([1.0; 4]).draw_pattern(
+            &Pattern::new([4.0, 4.0]),
+            &texture,
+            [0.0, 0.0, 20.0, 20.0],
+            &c.draw_state,
+            c.transform,
+            &mut list,
+        );
+        assert!(list.is_empty());
+    }
+}
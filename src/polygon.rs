@@ -1,23 +1,70 @@
 //! Draw polygon
 
 use crate::{
-    math::{Matrix2d, Scalar},
+    gradient::Gradient,
+    image::Image,
+    math::{identity, is_convex, Matrix2d, Scalar},
+    path::{self, Path},
+    pattern::Pattern,
     triangulation, types,
-    types::Color,
+    types::{Color, SourceRectangle},
     DrawState, Graphics,
 };
 
+/// The method used to split a polygon into triangles.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Triangulation {
+    /// Uses a triangle fan for convex polygons and ear clipping otherwise.
+    ///
+    /// This checks whether the polygon is convex on every draw.
+    Auto,
+    /// A triangle fan from the first vertex.
+    ///
+    /// This is fastest, but only correct for convex polygons.
+    Fan,
+    /// Ear clipping, which is correct for concave polygons
+    /// that do not intersect themselves.
+    EarClipping,
+}
+
+/// The rule deciding which areas of a polygon with multiple contours are filled.
+///
+/// An area is inside when a ray from it crosses contour edges
+/// a number of times that satisfies the rule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    /// Fills areas crossed an odd number of times.
+    ///
+    /// Holes are cut out regardless of their orientation.
+    EvenOdd,
+    /// Fills areas where edges going up and down do not cancel out.
+    ///
+    /// Holes must have the opposite orientation of the outer contour.
+    NonZero,
+}
+
 /// A polygon
 #[derive(Copy, Clone)]
 pub struct Polygon {
     /// The color of the polygon
     pub color: Color,
+    /// The triangulation method
+    pub triangulation: Triangulation,
+    /// The fill rule used for polygons with multiple contours
+    pub fill_rule: FillRule,
 }
 
 impl Polygon {
     /// Creates new polygon
+    ///
+    /// Uses a triangle fan, which is only correct for convex polygons.
+    /// Concave polygons need `Triangulation::Auto` or `Triangulation::EarClipping`.
     pub fn new(color: Color) -> Polygon {
-        Polygon { color }
+        Polygon {
+            color,
+            triangulation: Triangulation::Fan,
+            fill_rule: FillRule::EvenOdd,
+        }
     }
 
     /// Sets color.
@@ -26,6 +73,18 @@ impl Polygon {
         self
     }
 
+    /// Sets triangulation method.
+    pub fn triangulation(mut self, value: Triangulation) -> Self {
+        self.triangulation = value;
+        self
+    }
+
+    /// Sets fill rule.
+    pub fn fill_rule(mut self, value: FillRule) -> Self {
+        self.fill_rule = value;
+        self
+    }
+
     /// Draws polygon using the default method.
     #[inline(always)]
     pub fn draw<G>(
@@ -51,10 +110,143 @@ impl Polygon {
         G: Graphics,
     {
         g.tri_list(draw_state, &self.color, |f| {
-            triangulation::with_polygon_tri_list(transform, polygon, |vertices| f(vertices))
+            self.tri_list(transform, polygon, |vertices| f(vertices))
+        });
+    }
+
+    /// Draws polygon filled with a gradient.
+    ///
+    /// The color of the polygon is ignored.
+    pub fn draw_gradient<G>(
+        &self,
+        gradient: &Gradient,
+        polygon: types::Polygon<'_>,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        gradient.draw_tri_list(draw_state, transform, g, |f| {
+            self.tri_list(identity(), polygon, |vertices| f(vertices))
+        });
+    }
+
+    /// Draws polygon filled with a repeating texture pattern.
+    ///
+    /// The color of the polygon is ignored.
+    pub fn draw_pattern<G>(
+        &self,
+        pattern: &Pattern,
+        texture: &<G as Graphics>::Texture,
+        polygon: types::Polygon<'_>,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        pattern.draw_tri_list(texture, draw_state, transform, g, |f| {
+            self.tri_list(identity(), polygon, |vertices| f(vertices))
         });
     }
 
+    /// Draws polygon filled with a texture.
+    ///
+    /// The source rectangle, or the whole texture when `None`,
+    /// is stretched over the bounding box of the polygon.
+    /// The texture is multiplied with the color of the polygon.
+    pub fn draw_texture<G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        source_rectangle: Option<SourceRectangle>,
+        polygon: types::Polygon<'_>,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let first = match polygon.first() {
+            Some(&first) => first,
+            None => return,
+        };
+        let (min, max) = polygon.iter().fold((first, first), |(min, max), p| {
+            (
+                [min[0].min(p[0]), min[1].min(p[1])],
+                [max[0].max(p[0]), max[1].max(p[1])],
+            )
+        });
+        Image::new_color(self.color)
+            .maybe_src_rect(source_rectangle)
+            .rect([min[0], min[1], max[0] - min[0], max[1] - min[1]])
+            .draw_tri_list(texture, draw_state, transform, g, |f| {
+                self.tri_list(identity(), polygon, |vertices| f(vertices))
+            });
+    }
+
+    /// Streams the triangles using the triangulation method.
+    fn tri_list<F>(&self, transform: Matrix2d, polygon: types::Polygon<'_>, f: F)
+    where
+        F: FnMut(&[[f32; 2]]),
+    {
+        let fan = match self.triangulation {
+            Triangulation::Auto => is_convex(polygon),
+            Triangulation::Fan => true,
+            Triangulation::EarClipping => false,
+        };
+        if fan {
+            triangulation::with_polygon_tri_list(transform, polygon, f)
+        } else {
+            triangulation::with_polygon_ear_clipping_tri_list(transform, polygon, f)
+        }
+    }
+
+    /// Draws polygon with multiple contours using the default method.
+    ///
+    /// This can be used to draw polygons with holes.
+    #[inline(always)]
+    pub fn draw_contours<G>(
+        &self,
+        contours: types::Contours<'_>,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        g.polygon_contours(self, contours, draw_state, transform);
+    }
+
+    /// Draws polygon with multiple contours using triangulation.
+    pub fn draw_contours_tri<G>(
+        &self,
+        contours: types::Contours<'_>,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        g.tri_list(draw_state, &self.color, |f| {
+            triangulation::with_contours_tri_list(transform, contours, self.fill_rule, |vertices| {
+                f(vertices)
+            })
+        });
+    }
+
+    /// Draws the inside of a path using the fill rule.
+    ///
+    /// Open sub-paths are closed with a straight line.
+    pub fn draw_path<G>(&self, path: &Path, draw_state: &DrawState, transform: Matrix2d, g: &mut G)
+    where
+        G: Graphics,
+    {
+        let sub_paths = path.flatten(path::tolerance(transform));
+        let contours: Vec<types::Polygon<'_>> = sub_paths.iter().map(|s| &s.points[..]).collect();
+        self.draw_contours(&contours, draw_state, transform, g);
+    }
+
     /// Draws tweened polygon with linear interpolation, using default method.
     #[inline(always)]
     pub fn draw_tween_lerp<G>(
@@ -103,4 +295,147 @@ mod test {
     fn test_polygon() {
         let _polygon = Polygon::new([1.0; 4]).color([0.0; 4]);
     }
+
+    #[test]
+    fn test_concave() {
+        use crate::{software::Canvas, Context};
+
+        let c = Context::new_abs(10.0, 10.0);
+        // A U-shape where a triangle fan covers the notch.
+        let polygon = [
+            [0.0, 0.0],
+            [10.0, 0.0],
+            [10.0, 10.0],
+            [7.0, 10.0],
+            [7.0, 3.0],
+            [3.0, 3.0],
+            [3.0, 10.0],
+            [0.0, 10.0],
+        ];
+        for &(triangulation, notch) in &[
+            (Triangulation::Fan, [255; 4]),
+            (Triangulation::Auto, [0; 4]),
+            (Triangulation::EarClipping, [0; 4]),
+        ] {
+            let mut canvas = Canvas::new(10, 10);
+            Polygon::new([1.0; 4]).triangulation(triangulation).draw(
+                &polygon,
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+            assert_eq!(canvas.pixel(5, 7), notch);
+            assert_eq!(canvas.pixel(1, 8), [255; 4]);
+            assert_eq!(canvas.pixel(8, 8), [255; 4]);
+            assert_eq!(canvas.pixel(5, 1), [255; 4]);
+            if triangulation != Triangulation::Fan {
+                let filled = canvas.pixels().chunks(4).filter(|p| p[3] != 0).count();
+                assert_eq!(filled, 100 - 4 * 7);
+            }
+        }
+    }
+
+    #[test]
+    fn test_contours() {
+        use crate::{software::Canvas, Context};
+
+        let c = Context::new_abs(12.0, 12.0);
+        let outer = [[0.0, 0.0], [12.0, 0.0], [12.0, 12.0], [0.0, 12.0]];
+        // Same orientation as the outer contour.
+        let hole = [[4.0, 4.0], [8.0, 4.0], [8.0, 8.0], [4.0, 8.0]];
+        let reversed = [[4.0, 4.0], [4.0, 8.0], [8.0, 8.0], [8.0, 4.0]];
+        for &(fill_rule, inner, center) in &[
+            (FillRule::EvenOdd, &hole, [0; 4]),
+            (FillRule::NonZero, &hole, [255; 4]),
+            (FillRule::EvenOdd, &reversed, [0; 4]),
+            (FillRule::NonZero, &reversed, [0; 4]),
+        ] {
+            let mut canvas = Canvas::new(12, 12);
+            Polygon::new([1.0; 4]).fill_rule(fill_rule).draw_contours(
+                &[&outer, inner],
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+            assert_eq!(canvas.pixel(6, 6), center);
+            assert_eq!(canvas.pixel(2, 2), [255; 4]);
+            assert_eq!(canvas.pixel(9, 6), [255; 4]);
+            let filled = canvas.pixels().chunks(4).filter(|p| p[3] != 0).count();
+            assert_eq!(filled, if center[3] == 0 { 144 - 16 } else { 144 });
+        }
+    }
+
+    #[test]
+    fn test_self_intersecting() {
+        use crate::{software::Canvas, Context};
+
+        let c = Context::new_abs(10.0, 10.0);
+        // A pentagram, where the center is covered twice.
+        let star: Vec<[f64; 2]> = (0..5)
+            .map(|i| {
+                let angle = (i * 2) as f64 * std::f64::consts::PI * 2.0 / 5.0;
+                [5.0 + 5.0 * angle.sin(), 5.0 - 5.0 * angle.cos()]
+            })
+            .collect();
+        for &(fill_rule, center) in &[(FillRule::EvenOdd, [0; 4]), (FillRule::NonZero, [255; 4])] {
+            let mut canvas = Canvas::new(10, 10);
+            Polygon::new([1.0; 4]).fill_rule(fill_rule).draw_contours(
+                &[&star],
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+            assert_eq!(canvas.pixel(5, 5), center);
+            assert_eq!(canvas.pixel(5, 2), [255; 4]);
+        }
+    }
+
+    #[test]
+    fn test_contours_nan() {
+        use crate::{software::Canvas, Context};
+
+        // Edges with a NaN end are skipped, leaving a single edge.
+        let contour = [[0.0, 0.0], [10.0, f64::NAN], [10.0, 10.0], [0.0, 10.0]];
+        let mut count = 0;
+        triangulation::with_contours_tri_list(identity(), &[&contour], FillRule::EvenOdd, |v| {
+            count += v.len()
+        });
+        assert_eq!(count, 0);
+
+        // Other contours are still filled.
+        let c = Context::new_abs(10.0, 10.0);
+        let nan = [[0.0, 0.0], [f64::NAN, 2.0], [10.0, 4.0], [0.0, 4.0]];
+        let square = [[0.0, 6.0], [10.0, 6.0], [10.0, 10.0], [0.0, 10.0]];
+        let mut canvas = Canvas::new(10, 10);
+        Polygon::new([1.0; 4]).draw_contours(
+            &[&nan, &square],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(5, 8), [255; 4]);
+        assert_eq!(canvas.pixel(5, 5), [0; 4]);
+    }
+
+    #[test]
+    fn test_contours_grid() {
+        // A map of 20x20 regions, where every row is a single sweep step.
+        let squares: Vec<[[f64; 2]; 4]> = (0..400)
+            .map(|i| {
+                let (x, y) = ((i % 20) as f64, (i / 20) as f64);
+                [[x, y], [x + 1.0, y], [x + 1.0, y + 1.0], [x, y + 1.0]]
+            })
+            .collect();
+        let contours: Vec<&[[f64; 2]]> = squares.iter().map(|s| &s[..]).collect();
+        let (mut count, mut area) = (0, 0.0);
+        triangulation::with_contours_tri_list(identity(), &contours, FillRule::NonZero, |v| {
+            count += v.len();
+            for t in v.chunks(3) {
+                let (a, b, c) = (t[0], t[1], t[2]);
+                area += 0.5 * ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs();
+            }
+        });
+        assert_eq!(count, 400 * 6);
+        assert!((area - 400.0).abs() < 1e-3);
+    }
 }
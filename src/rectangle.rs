@@ -15,12 +15,20 @@
 
 pub use crate::math::margin_rectangle as margin;
 use crate::{
-    math::{Matrix2d, Scalar},
+    gradient::Gradient,
+    image::Image,
+    line::{self, Dash, Join},
+    math::{identity, Matrix2d, Scalar, Vec2d},
+    pattern::Pattern,
+    radians::Radians,
     triangulation, types,
-    types::{Color, Radius, Resolution},
-    DrawState, Graphics,
+    types::{Color, Radius, Resolution, SourceRectangle},
+    DrawState, Graphics, BACK_END_MAX_VERTEX_COUNT as BUFFER_SIZE,
 };
 
+/// The number of bands with different alpha in the soft edge of a shadow.
+const SHADOW_BANDS: usize = 4;
+
 /// Create `types::Rectangle` by the two opposite corners.
 ///
 /// The corners are in (x0, y0) and (x1, y1).
@@ -96,6 +104,24 @@ pub struct Border {
     /// The radius of the border. The half-width of the line by which border is
     /// drawn.
     pub radius: Radius,
+    /// The dash pattern of the border
+    ///
+    /// Dashes have `line::Shape::Square` ends,
+    /// and bend around corners with `Join::Miter(4.0)`.
+    pub dash: Option<Dash>,
+}
+
+/// A soft shadow behind the rectangle
+#[derive(Copy, Clone)]
+pub struct Shadow {
+    /// The color of the shadow
+    pub color: Color,
+    /// The offset of the shadow from the rectangle
+    pub offset: Vec2d,
+    /// The width of the soft edge, centered on the outline of the shadow
+    pub blur: Radius,
+    /// The distance the shadow extends beyond the rectangle
+    pub spread: Radius,
 }
 
 /// A filled rectangle
@@ -107,6 +133,8 @@ pub struct Rectangle {
     pub shape: Shape,
     /// The border
     pub border: Option<Border>,
+    /// The shadow
+    pub shadow: Option<Shadow>,
 }
 
 impl Rectangle {
@@ -116,6 +144,7 @@ impl Rectangle {
             color,
             shape: Shape::Square,
             border: None,
+            shadow: None,
         }
     }
 
@@ -125,6 +154,7 @@ impl Rectangle {
             color,
             shape: Shape::Round(round_radius, 32),
             border: None,
+            shadow: None,
         }
     }
 
@@ -133,7 +163,12 @@ impl Rectangle {
         Rectangle {
             color: [0.0; 4],
             shape: Shape::Square,
-            border: Some(Border { color, radius }),
+            border: Some(Border {
+                color,
+                radius,
+                dash: None,
+            }),
+            shadow: None,
         }
     }
 
@@ -149,7 +184,9 @@ impl Rectangle {
             border: Some(Border {
                 color,
                 radius: border_radius,
+                dash: None,
             }),
+            shadow: None,
         }
     }
 
@@ -177,6 +214,18 @@ impl Rectangle {
         self
     }
 
+    /// Sets shadow properties.
+    pub fn shadow(mut self, value: Shadow) -> Self {
+        self.shadow = Some(value);
+        self
+    }
+
+    /// Sets optional shadow.
+    pub fn maybe_shadow(mut self, value: Option<Shadow>) -> Self {
+        self.shadow = value;
+        self
+    }
+
     /// Draws the rectangle by corners using the default method.
     #[inline(always)]
     pub fn draw_from_to<P: Into<types::Vec2d>, G>(
@@ -218,6 +267,106 @@ impl Rectangle {
         g.rectangle(self, rectangle, draw_state, transform);
     }
 
+    /// Draws the rectangle filled with a gradient.
+    ///
+    /// The color of the rectangle is ignored.
+    /// The border is drawn with its own color.
+    pub fn draw_gradient<R: Into<types::Rectangle>, G>(
+        &self,
+        gradient: &Gradient,
+        rectangle: R,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let rectangle = rectangle.into();
+        if let Some(shadow) = self.shadow {
+            self.draw_shadow_tri(shadow, rectangle, draw_state, transform, g);
+        }
+        gradient.draw_tri_list(draw_state, transform, g, |f| {
+            self.fill_tri_list(rectangle, |vertices| f(vertices))
+        });
+        self.draw_border(rectangle, draw_state, transform, g);
+    }
+
+    /// Draws the rectangle filled with a repeating texture pattern.
+    ///
+    /// The color of the rectangle is ignored.
+    /// The border is drawn with its own color.
+    pub fn draw_pattern<R: Into<types::Rectangle>, G>(
+        &self,
+        pattern: &Pattern,
+        texture: &<G as Graphics>::Texture,
+        rectangle: R,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let rectangle = rectangle.into();
+        if let Some(shadow) = self.shadow {
+            self.draw_shadow_tri(shadow, rectangle, draw_state, transform, g);
+        }
+        pattern.draw_tri_list(texture, draw_state, transform, g, |f| {
+            self.fill_tri_list(rectangle, |vertices| f(vertices))
+        });
+        self.draw_border(rectangle, draw_state, transform, g);
+    }
+
+    /// Draws the rectangle filled with a texture.
+    ///
+    /// The source rectangle, or the whole texture when `None`,
+    /// is stretched over the rectangle, clipped by the shape of the corners.
+    /// The texture is multiplied with the color of the rectangle.
+    /// The border is drawn with its own color.
+    pub fn draw_texture<R: Into<types::Rectangle>, G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        source_rectangle: Option<SourceRectangle>,
+        rectangle: R,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let rectangle = rectangle.into();
+        if let Some(shadow) = self.shadow {
+            self.draw_shadow_tri(shadow, rectangle, draw_state, transform, g);
+        }
+        Image::new_color(self.color)
+            .maybe_src_rect(source_rectangle)
+            .rect(rectangle)
+            .draw_tri_list(texture, draw_state, transform, g, |f| {
+                self.fill_tri_list(rectangle, |vertices| f(vertices))
+            });
+        self.draw_border(rectangle, draw_state, transform, g);
+    }
+
+    /// Streams the triangles of the fill before transform.
+    fn fill_tri_list<F>(&self, rectangle: types::Rectangle, mut f: F)
+    where
+        F: FnMut(&[[f32; 2]]),
+    {
+        let m = identity();
+        match self.shape {
+            Shape::Square => f(&triangulation::rect_tri_list_xy(m, rectangle)),
+            Shape::Round(round_radius, resolution) => triangulation::with_round_rectangle_tri_list(
+                resolution,
+                m,
+                rectangle,
+                round_radius,
+                f,
+            ),
+            Shape::Bevel(bevel_radius) => {
+                triangulation::with_round_rectangle_tri_list(2, m, rectangle, bevel_radius, f)
+            }
+        }
+    }
+
     /// Draws the rectangle using triangulation.
     ///
     /// This is the default implementation of draw() that will be used if `G`
@@ -232,6 +381,9 @@ impl Rectangle {
         G: Graphics,
     {
         let rectangle = rectangle.into();
+        if let Some(shadow) = self.shadow {
+            self.draw_shadow_tri(shadow, rectangle, draw_state, transform, g);
+        }
         if self.color[3] != 0.0 {
             match self.shape {
                 Shape::Square => {
@@ -240,38 +392,79 @@ impl Rectangle {
                     });
                 }
                 Shape::Round(round_radius, resolution) => {
-                    g.tri_list(draw_state, &self.color, |f| {
-                        triangulation::with_round_rectangle_tri_list(
+                    g.tri_list_indexed(draw_state, &self.color, |f| {
+                        triangulation::with_round_rectangle_tri_list_indexed(
                             resolution,
                             transform,
                             rectangle,
                             round_radius,
-                            |vertices| f(vertices),
+                            |vertices, indices| f(vertices, indices),
                         )
                     });
                 }
                 Shape::Bevel(bevel_radius) => {
-                    g.tri_list(draw_state, &self.color, |f| {
-                        triangulation::with_round_rectangle_tri_list(
+                    g.tri_list_indexed(draw_state, &self.color, |f| {
+                        triangulation::with_round_rectangle_tri_list_indexed(
                             2,
                             transform,
                             rectangle,
                             bevel_radius,
-                            |vertices| f(vertices),
+                            |vertices, indices| f(vertices, indices),
                         )
                     });
                 }
             }
         }
+        self.draw_border(rectangle, draw_state, transform, g);
+    }
 
+    /// Draws the border of the rectangle, if any.
+    fn draw_border<G>(
+        &self,
+        rectangle: types::Rectangle,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
         if let Some(Border {
             color,
             radius: border_radius,
+            dash,
         }) = self.border
         {
             if color[3] == 0.0 {
                 return;
             }
+            if let Some(dash) = dash {
+                let points: Vec<types::Vec2d> = match self.shape {
+                    Shape::Square => {
+                        let (x, y, w, h) = (rectangle[0], rectangle[1], rectangle[2], rectangle[3]);
+                        vec![[x, y], [x + w, y], [x + w, y + h], [x, y + h]]
+                    }
+                    Shape::Round(round_radius, resolution) => {
+                        triangulation::round_rectangle_points(resolution, rectangle, round_radius)
+                            .collect()
+                    }
+                    Shape::Bevel(bevel_radius) => {
+                        triangulation::round_rectangle_points(2, rectangle, bevel_radius).collect()
+                    }
+                };
+                g.tri_list(draw_state, &color, |f| {
+                    triangulation::with_dashed_polyline_tri_list(
+                        transform,
+                        &points,
+                        true,
+                        border_radius,
+                        dash,
+                        Join::Miter(4.0),
+                        line::Shape::Square,
+                        |vertices| f(vertices),
+                    )
+                });
+                return;
+            }
             match self.shape {
                 Shape::Square => {
                     g.tri_list(draw_state, &color, |f| {
@@ -309,6 +502,131 @@ impl Rectangle {
             }
         }
     }
+
+    /// Draws the shadow of the rectangle using triangulation.
+    ///
+    /// The alpha of the shadow color fades out across the blur width,
+    /// using vertex colors to approximate a blurred edge.
+    fn draw_shadow_tri<G>(
+        &self,
+        shadow: Shadow,
+        rectangle: types::Rectangle,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        if shadow.color[3] == 0.0 {
+            return;
+        }
+        let (x, y, w, h) = (
+            rectangle[0] + shadow.offset[0],
+            rectangle[1] + shadow.offset[1],
+            rectangle[2],
+            rectangle[3],
+        );
+        let (round_radius, resolution) = match self.shape {
+            // Square corners are rounded by the blur.
+            Shape::Square => (0.0, 8),
+            Shape::Round(round_radius, resolution) => (round_radius, resolution.max(2)),
+            Shape::Bevel(bevel_radius) => (bevel_radius, 2),
+        };
+        let round_radius = round_radius.min(0.5 * w.abs()).min(0.5 * h.abs()).max(0.0);
+        // Every outline is at a distance from the same core rectangle.
+        // Spread keeps square corners square, and makes round corners larger.
+        let (grow, edge) = if round_radius > 0.0 {
+            (-round_radius, round_radius + shadow.spread)
+        } else {
+            (shadow.spread, 0.0)
+        };
+        let half_blur = 0.5 * shadow.blur.max(0.0);
+        let (cw, ch) = ((w + 2.0 * grow).max(0.0), (h + 2.0 * grow).max(0.0));
+        let edge = edge.max(0.0);
+        // Shrink the core so the blur can extend inside the outline.
+        let shrink = (half_blur - edge).max(0.0).min(0.5 * cw).min(0.5 * ch);
+        let (cw, ch, edge) = (cw - 2.0 * shrink, ch - 2.0 * shrink, edge + shrink);
+        let core = [x + 0.5 * (w - cw), y + 0.5 * (h - ch), cw, ch];
+        let inner = (edge - half_blur).max(0.0);
+        let bands = if half_blur > 0.0 { SHADOW_BANDS } else { 0 };
+        let color_at = |distance: Scalar| {
+            let mut color = shadow.color;
+            if half_blur > 0.0 {
+                let t = ((distance - edge + half_blur) / (2.0 * half_blur)).clamp(0.0, 1.0);
+                color[3] *= (1.0 - t * t * (3.0 - 2.0 * t)) as f32;
+            }
+            color
+        };
+        let distance = |band: usize| {
+            let outer = edge + half_blur;
+            inner + (outer - inner) * band as Scalar / bands.max(1) as Scalar
+        };
+        let center = [core[0] + 0.5 * core[2], core[1] + 0.5 * core[3]];
+        g.tri_list_c(draw_state, |f| {
+            let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+            let mut colors: [[f32; 4]; BUFFER_SIZE] = [[0.0; 4]; BUFFER_SIZE];
+            let mut i = 0;
+            let mut emit = |p: Vec2d, color: Color| {
+                vertices[i] = [
+                    triangulation::tx(transform, p[0], p[1]),
+                    triangulation::ty(transform, p[0], p[1]),
+                ];
+                colors[i] = color;
+                i += 1;
+                // The buffer size is a multiple of 3.
+                if i == BUFFER_SIZE {
+                    f(&vertices, &colors);
+                    i = 0;
+                }
+            };
+            let ring: Vec<Vec2d> = shadow_points(core, inner, resolution).collect();
+            let color = color_at(inner);
+            for j in 0..ring.len() {
+                emit(center, color);
+                emit(ring[j], color);
+                emit(ring[(j + 1) % ring.len()], color);
+            }
+            let mut ring = ring;
+            for band in 1..=bands {
+                let d = distance(band);
+                let next: Vec<Vec2d> = shadow_points(core, d, resolution).collect();
+                let (a, b) = (color_at(distance(band - 1)), color_at(d));
+                let n = ring.len();
+                for j in 0..n {
+                    let k = (j + 1) % n;
+                    emit(ring[j], a);
+                    emit(ring[k], a);
+                    emit(next[j], b);
+                    emit(ring[k], a);
+                    emit(next[k], b);
+                    emit(next[j], b);
+                }
+                ring = next;
+            }
+            if i > 0 {
+                f(&vertices[0..i], &colors[0..i]);
+            }
+        });
+    }
+}
+
+/// Returns the points of the outline at a distance around a core rectangle.
+///
+/// Each corner is a quarter circle with `resolution` points.
+fn shadow_points(
+    core: types::Rectangle,
+    distance: Scalar,
+    resolution: Resolution,
+) -> impl Iterator<Item = Vec2d> {
+    let (x, y, w, h) = (core[0], core[1], core[2], core[3]);
+    let corners = [[x + w, y + h], [x, y + h], [x, y], [x + w, y]];
+    (0..4 * resolution).map(move |j| {
+        let (corner, k) = ((j / resolution) as usize, j % resolution);
+        let angle = (corner as Scalar + k as Scalar / (resolution - 1) as Scalar)
+            * <Scalar as Radians>::_90();
+        let c = corners[corner];
+        [c[0] + angle.cos() * distance, c[1] + angle.sin() * distance]
+    })
 }
 
 #[cfg(test)]
@@ -323,6 +641,7 @@ mod test {
             .border(Border {
                 color: [0.0; 4],
                 radius: 4.0,
+                dash: None,
             });
     }
 
@@ -337,4 +656,101 @@ mod test {
             [1.0, -1.0, 1.0, 4.0]
         );
     }
+
+    #[test]
+    fn test_shadow() {
+        use crate::{software::Canvas, Context};
+
+        let c = Context::new_abs(40.0, 40.0);
+        let shadow = Shadow {
+            color: [0.0, 0.0, 0.0, 1.0],
+            offset: [2.0, 2.0],
+            blur: 4.0,
+            spread: 0.0,
+        };
+        let mut canvas = Canvas::new(40, 40);
+        Rectangle::new([0.0; 4]).shadow(shadow).draw(
+            [8.0, 8.0, 20.0, 20.0],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        let alpha = |x, y| canvas.pixel(x, y)[3];
+        assert_eq!(alpha(20, 20), 255);
+        // Half transparent at the edge of the offset rectangle, at x = 10.
+        assert!(((alpha(9, 20) as i32 + alpha(10, 20) as i32) / 2 - 128).abs() <= 8);
+        assert!(alpha(11, 20) > alpha(10, 20));
+        assert!(alpha(9, 20) < alpha(10, 20));
+        assert_eq!(alpha(7, 20), 0);
+        // The blur rounds the corners.
+        assert!(alpha(10, 10) < alpha(10, 20));
+
+        // Round corners and spread.
+        let mut canvas = Canvas::new(40, 40);
+        Rectangle::new_round([0.0; 4], 8.0)
+            .shadow(Shadow {
+                blur: 0.0,
+                spread: 2.0,
+                ..shadow
+            })
+            .draw(
+                [8.0, 8.0, 20.0, 20.0],
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+        assert_eq!(canvas.pixel(8, 20)[3], 255);
+        assert_eq!(canvas.pixel(8, 8)[3], 0);
+        assert_eq!(canvas.pixel(20, 31)[3], 255);
+        assert_eq!(canvas.pixel(20, 33)[3], 0);
+    }
+
+    #[test]
+    fn test_draw_texture() {
+        use crate::{
+            software::{Canvas, Texture},
+            Context,
+        };
+
+        let texture = Texture::from_rgba8(1, 1, vec![0, 0, 255, 255]).unwrap();
+        let c = Context::new_abs(20.0, 20.0);
+        let mut canvas = Canvas::new(20, 20);
+        Rectangle::new_round([1.0; 4], 8.0).draw_texture(
+            &texture,
+            None,
+            [0.0, 0.0, 20.0, 20.0],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(0, 0), [0; 4]);
+        assert_eq!(canvas.pixel(10, 0), [0, 0, 255, 255]);
+        assert_eq!(canvas.pixel(19, 10), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_dashed_border() {
+        use crate::{software::Canvas, Context};
+
+        let c = Context::new_abs(20.0, 20.0);
+        let mut canvas = Canvas::new(20, 20);
+        Rectangle::new([0.0; 4])
+            .border(Border {
+                color: [1.0; 4],
+                radius: 1.0,
+                dash: Some(Dash::new(4.0, 4.0)),
+            })
+            .draw(
+                [2.0, 2.0, 16.0, 16.0],
+                &c.draw_state,
+                c.transform,
+                &mut canvas,
+            );
+        // Dash along the top edge, starting at the corner.
+        assert_eq!(canvas.pixel(2, 2), [255; 4]);
+        assert_eq!(canvas.pixel(5, 2), [255; 4]);
+        assert_eq!(canvas.pixel(7, 2), [0; 4]);
+        assert_eq!(canvas.pixel(11, 2), [255; 4]);
+        assert_eq!(canvas.pixel(10, 10), [0; 4]);
+    }
 }
@@ -0,0 +1,263 @@
+//! Rich text.
+//!
+//! `RichText` is a sequence of spans on a shared baseline,
+//! each with its own color, font size and font.
+//! Fonts are indices into a slice of character caches,
+//! so spans can mix e.g. a regular and a bold font.
+//! Each span is drawn with `Text`.
+//!
+//! ```
+//! use graphics::{
+//!     character::{Character, CharacterCache},
+//!     rich_text::{RichText, Span},
+//!     software::{Canvas, Texture},
+//!     types::FontSize,
+//!     Context, Transformed,
+//! };
+//!
+//! /// A font where every character is a filled square.
+//! struct Squares(Texture);
+//!
+//! impl CharacterCache for Squares {
+//!     type Texture = Texture;
+//!     type Error = ();
+//!
+//!     fn character(&mut self, size: FontSize, _ch: char) -> Result<Character<'_, Texture>, ()> {
+//!         let size = size as f64;
+//!         Ok(Character {
+//!             offset: [0.0, size],
+//!             advance_size: [size, 0.0],
+//!             atlas_offset: [0.0, 0.0],
+//!             atlas_size: [size, size],
+//!             texture: &self.0,
+//!             is_invalid: false,
+//!         })
+//!     }
+//! }
+//!
+//! let text = RichText::new()
+//!     .span(Span::new("error: ", [1.0, 0.0, 0.0, 1.0], 14).font(1))
+//!     .span(Span::new("file not found", [0.0, 0.0, 0.0, 1.0], 14));
+//! let square = || Squares(Texture::from_rgba8(16, 16, vec![255; 16 * 16 * 4]).unwrap());
+//! let mut fonts = [square(), square()];
+//! let c = Context::new_abs(300.0, 40.0);
+//! let mut canvas = Canvas::new(300, 40);
+//! text.draw(&mut fonts, &c.draw_state, c.transform.trans(10.0, 20.0), &mut canvas)
+//!     .unwrap();
+//! assert_eq!(canvas.pixel(12, 10), [255, 0, 0, 255]);
+//! assert_eq!(text.width(&mut fonts), Ok(294.0));
+//! ```
+
+use crate::{
+    character::{CharacterCache, VMetrics},
+    math::{Matrix2d, Scalar},
+    types::{Color, FontSize},
+    DrawState, Graphics, Text, Transformed,
+};
+
+/// A piece of text with the same style.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    /// The text.
+    pub text: String,
+    /// The color.
+    pub color: Color,
+    /// The font size.
+    pub font_size: FontSize,
+    /// The index of the character cache used for the font.
+    pub font: usize,
+}
+
+impl Span {
+    /// Creates a new span using the first font.
+    pub fn new(text: &str, color: Color, font_size: FontSize) -> Span {
+        Span {
+            text: text.to_string(),
+            color,
+            font_size,
+            font: 0,
+        }
+    }
+
+    /// Sets the index of the character cache used for the font.
+    pub fn font(mut self, value: usize) -> Self {
+        self.font = value;
+        self
+    }
+}
+
+/// Text made of spans on a shared baseline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RichText {
+    /// The spans, from left to right.
+    pub spans: Vec<Span>,
+    /// Whether the position of characters should be rounded.
+    pub round: bool,
+}
+
+impl RichText {
+    /// Creates a new rich text without spans.
+    pub fn new() -> RichText {
+        RichText {
+            spans: vec![],
+            round: false,
+        }
+    }
+
+    /// Adds a span.
+    pub fn span(mut self, span: Span) -> Self {
+        self.spans.push(span);
+        self
+    }
+
+    /// A builder method indicating that the position of characters should be rounded.
+    pub fn round(mut self) -> Self {
+        self.round = true;
+        self
+    }
+
+    /// Returns the horizontal offsets of the spans and the total width.
+    ///
+    /// Characters are kerned across spans with the same font and font size.
+    fn offsets<C>(&self, caches: &mut [C]) -> Result<(Vec<Scalar>, Scalar), C::Error>
+    where
+        C: CharacterCache,
+    {
+        let mut offsets = Vec::with_capacity(self.spans.len());
+        let mut x = 0.0;
+        let mut prev: Option<(&Span, char)> = None;
+        for span in &self.spans {
+            let cache = &mut caches[span.font];
+            if let (Some((prev, last)), Some(first)) = (prev, span.text.chars().next()) {
+                if prev.font == span.font && prev.font_size == span.font_size {
+                    x += cache.kerning(span.font_size, last, first)?;
+                }
+            }
+            offsets.push(x);
+            x += cache.width(span.font_size, &span.text)?;
+            if let Some(last) = span.text.chars().last() {
+                prev = Some((span, last));
+            }
+        }
+        Ok((offsets, x))
+    }
+
+    /// Returns the width of the text.
+    ///
+    /// Panics if a span uses a font without character cache.
+    pub fn width<C>(&self, caches: &mut [C]) -> Result<Scalar, C::Error>
+    where
+        C: CharacterCache,
+    {
+        Ok(self.offsets(caches)?.1)
+    }
+
+    /// Returns the vertical metrics covering all spans.
+    ///
+    /// Panics if a span uses a font without character cache.
+    pub fn v_metrics<C>(&self, caches: &mut [C]) -> Result<VMetrics, C::Error>
+    where
+        C: CharacterCache,
+    {
+        let mut res = VMetrics {
+            ascent: 0.0,
+            descent: 0.0,
+            line_gap: 0.0,
+        };
+        for span in &self.spans {
+            let v_metrics = caches[span.font].v_metrics(span.font_size)?;
+            res.ascent = res.ascent.max(v_metrics.ascent);
+            res.descent = res.descent.min(v_metrics.descent);
+            res.line_gap = res.line_gap.max(v_metrics.line_gap);
+        }
+        Ok(res)
+    }
+
+    /// Draws rich text with character caches.
+    ///
+    /// The origin is on the baseline, like for `Text::draw`.
+    /// Panics if a span uses a font without character cache.
+    pub fn draw<C, G>(
+        &self,
+        caches: &mut [C],
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) -> Result<(), C::Error>
+    where
+        C: CharacterCache,
+        G: Graphics<Texture = <C as CharacterCache>::Texture>,
+    {
+        let (offsets, _) = self.offsets(caches)?;
+        for (span, &x) in self.spans.iter().zip(&offsets) {
+            let mut text = Text::new_color(span.color, span.font_size);
+            text.round = self.round;
+            text.draw(
+                &span.text,
+                &mut caches[span.font],
+                draw_state,
+                transform.trans(x, 0.0),
+                g,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for RichText {
+    fn default() -> Self {
+        RichText::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{software::Canvas, text_layout::test::MonoCache, Context};
+
+    #[test]
+    fn test_rich_text() {
+        let (red, blue) = ([1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]);
+        let text = RichText::new()
+            .span(Span::new("ab", red, 10))
+            .span(Span::new("c", blue, 20).font(1));
+        let mut caches = [MonoCache::new(), MonoCache::new()];
+        assert_eq!(text.width(&mut caches), Ok(30.0));
+        assert_eq!(text.v_metrics(&mut caches).unwrap().ascent, 20.0);
+
+        let c = Context::new_abs(40.0, 30.0);
+        let mut canvas = Canvas::new(40, 30);
+        text.draw(
+            &mut caches,
+            &c.draw_state,
+            c.transform.trans(0.0, 20.0),
+            &mut canvas,
+        )
+        .unwrap();
+        assert_eq!(canvas.pixel(5, 15), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(15, 15), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(25, 15), [0, 0, 255, 255]);
+        assert_eq!(canvas.pixel(5, 25), [0; 4]);
+    }
+
+    #[test]
+    fn test_kerning() {
+        let color = [1.0; 4];
+        let mut caches = [MonoCache::new(), MonoCache::new()];
+        let width = |text: RichText, caches: &mut [MonoCache]| text.width(caches).unwrap();
+        let text = RichText::new().span(Span::new("A", color, 10));
+        assert_eq!(
+            width(text.clone().span(Span::new("V", color, 10)), &mut caches),
+            17.0
+        );
+        // Spans with different fonts or sizes are not kerned.
+        assert_eq!(
+            width(text.clone().span(Span::new("V", color, 12)), &mut caches),
+            20.0
+        );
+        assert_eq!(
+            width(text.span(Span::new("V", color, 10).font(1)), &mut caches),
+            20.0
+        );
+    }
+}
@@ -0,0 +1,522 @@
+//! A software rasterizer back-end.
+//!
+//! Renders into an in-memory RGBA8 framebuffer on the CPU.
+//! This is useful for rendering without a window, e.g. on a server
+//! or when testing drawing code.
+//!
+//! The framebuffer uses the same coordinate system as the GPU back-ends:
+//! Vertices are in normalized device coordinates, where `[-1.0, 1.0]`
+//! is the upper left corner and `[1.0, -1.0]` is the lower right corner.
+//! Use `Context::new_abs` to draw in pixel coordinates.
+//!
+//! ```
+//! use graphics::{software::Canvas, Context, Rectangle};
+//!
+//! let mut canvas = Canvas::new(64, 64);
+//! let c = Context::new_abs(64.0, 64.0);
+//! graphics::clear([1.0; 4], &mut canvas);
+//! Rectangle::new([1.0, 0.0, 0.0, 1.0]).draw(
+//!     [0.0, 0.0, 32.0, 32.0],
+//!     &c.draw_state,
+//!     c.transform,
+//!     &mut canvas,
+//! );
+//! assert_eq!(canvas.pixel(8, 8), [255, 0, 0, 255]);
+//! assert_eq!(canvas.pixel(48, 48), [255; 4]);
+//! ```
+
+use texture::{CreateTexture, Format, TextureOp, TextureSettings, UpdateTexture};
+
+use crate::{
+    draw_state::{Blend, Stencil},
+    types::Color,
+    DrawState, Graphics, ImageSize,
+};
+
+/// A RGBA8 texture stored in memory.
+///
+/// Pixels are stored row by row, starting with the top row.
+/// Texture coordinates are sampled using the nearest texel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Texture {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Texture {
+    /// Creates a new texture from RGBA8 pixels.
+    ///
+    /// Returns `None` if the buffer does not match the size.
+    pub fn from_rgba8(width: u32, height: u32, pixels: Vec<u8>) -> Option<Texture> {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        Some(Texture {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Returns the RGBA8 pixels.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Gets the color at texture coordinates.
+    fn sample(&self, uv: [f32; 2]) -> Color {
+        if self.width == 0 || self.height == 0 {
+            return [0.0; 4];
+        }
+        let x = ((uv[0] * self.width as f32) as i64)
+            .max(0)
+            .min(self.width as i64 - 1);
+        let y = ((uv[1] * self.height as f32) as i64)
+            .max(0)
+            .min(self.height as i64 - 1);
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let p = &self.pixels[i..i + 4];
+        let inv_255 = 1.0 / 255.0;
+        [
+            p[0] as f32 * inv_255,
+            p[1] as f32 * inv_255,
+            p[2] as f32 * inv_255,
+            p[3] as f32 * inv_255,
+        ]
+    }
+}
+
+impl ImageSize for Texture {
+    fn get_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+impl TextureOp<()> for Texture {
+    type Error = String;
+}
+
+impl CreateTexture<()> for Texture {
+    fn create<S: Into<[u32; 2]>>(
+        _factory: &mut (),
+        _format: Format,
+        memory: &[u8],
+        size: S,
+        _settings: &TextureSettings,
+    ) -> Result<Self, Self::Error> {
+        let [w, h] = size.into();
+        Texture::from_rgba8(w, h, memory.to_vec())
+            .ok_or_else(|| "memory does not match texture size".into())
+    }
+}
+
+impl UpdateTexture<()> for Texture {
+    fn update<O, S>(
+        &mut self,
+        _factory: &mut (),
+        _format: Format,
+        memory: &[u8],
+        offset: O,
+        size: S,
+    ) -> Result<(), Self::Error>
+    where
+        O: Into<[u32; 2]>,
+        S: Into<[u32; 2]>,
+    {
+        let [x, y] = offset.into();
+        let [w, h] = size.into();
+        if x + w > self.width || y + h > self.height {
+            return Err("update is outside texture".into());
+        }
+        if memory.len() != w as usize * h as usize * 4 {
+            return Err("memory does not match update size".into());
+        }
+        let row = w as usize * 4;
+        for j in 0..h as usize {
+            let dst = ((y as usize + j) * self.width as usize + x as usize) * 4;
+            self.pixels[dst..dst + row].copy_from_slice(&memory[j * row..(j + 1) * row]);
+        }
+        Ok(())
+    }
+}
+
+/// A framebuffer with a stencil buffer, rendered on the CPU.
+///
+/// Honors blend modes, scissor and stencil settings of `DrawState`.
+/// The scissor rectangle is in pixels with origin in the upper left corner.
+#[derive(Clone, Debug)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    stencil: Vec<u8>,
+}
+
+impl Canvas {
+    /// Creates a new canvas filled with transparent black.
+    pub fn new(width: u32, height: u32) -> Canvas {
+        let n = width as usize * height as usize;
+        Canvas {
+            width,
+            height,
+            pixels: vec![0; n * 4],
+            stencil: vec![0; n],
+        }
+    }
+
+    /// Returns the width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the RGBA8 pixels, row by row starting with the top row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Gets the RGBA8 color of a pixel.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Gets the stencil value of a pixel.
+    pub fn stencil(&self, x: u32, y: u32) -> u8 {
+        self.stencil[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Consumes the canvas and returns a texture with its pixels.
+    pub fn into_texture(self) -> Texture {
+        Texture {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels,
+        }
+    }
+
+    /// Rasterizes a triangle in normalized device coordinates.
+    ///
+    /// Pixels are covered when their center is inside the triangle.
+    /// Uses a top-left fill rule, such that shared edges are drawn once.
+    /// The `shade` closure receives barycentric coordinates.
+    fn fill_triangle<S>(&mut self, draw_state: &DrawState, tri: &[[f32; 2]], mut shade: S)
+    where
+        S: FnMut([f32; 3]) -> Color,
+    {
+        let (w, h) = (self.width as f64, self.height as f64);
+        let mut p = [[0.0; 2]; 3];
+        for (p, v) in p.iter_mut().zip(tri) {
+            *p = [(v[0] as f64 + 1.0) * 0.5 * w, (1.0 - v[1] as f64) * 0.5 * h];
+        }
+        let edge = |a: [f64; 2], b: [f64; 2], x: f64, y: f64| {
+            (b[0] - a[0]) * (y - a[1]) - (b[1] - a[1]) * (x - a[0])
+        };
+        let area = edge(p[0], p[1], p[2][0], p[2][1]);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        // Make the orientation positive.
+        let (p, order) = if area < 0.0 {
+            ([p[0], p[2], p[1]], [0, 2, 1])
+        } else {
+            (p, [0, 1, 2])
+        };
+        let area = area.abs();
+        // Whether pixels exactly on an edge are inside.
+        let top_left = |a: [f64; 2], b: [f64; 2]| {
+            let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+            dy > 0.0 || (dy == 0.0 && dx < 0.0)
+        };
+        let edges = [(p[1], p[2]), (p[2], p[0]), (p[0], p[1])];
+        let bias = [
+            top_left(edges[0].0, edges[0].1),
+            top_left(edges[1].0, edges[1].1),
+            top_left(edges[2].0, edges[2].1),
+        ];
+
+        let (mut x0, mut y0) = (0, 0);
+        let (mut x1, mut y1) = (self.width, self.height);
+        if let Some([sx, sy, sw, sh]) = draw_state.scissor {
+            x0 = sx.min(x1);
+            y0 = sy.min(y1);
+            x1 = sx.saturating_add(sw).min(x1);
+            y1 = sy.saturating_add(sh).min(y1);
+        }
+        let min_x = p.iter().fold(w, |acc, p| acc.min(p[0]));
+        let max_x = p.iter().fold(0.0, |acc: f64, p| acc.max(p[0]));
+        let min_y = p.iter().fold(h, |acc, p| acc.min(p[1]));
+        let max_y = p.iter().fold(0.0, |acc: f64, p| acc.max(p[1]));
+        let x0 = x0.max((min_x - 0.5).floor().max(0.0) as u32);
+        let y0 = y0.max((min_y - 0.5).floor().max(0.0) as u32);
+        let x1 = x1.min((max_x + 0.5).ceil().max(0.0) as u32);
+        let y1 = y1.min((max_y + 0.5).ceil().max(0.0) as u32);
+
+        for y in y0..y1 {
+            let cy = y as f64 + 0.5;
+            for x in x0..x1 {
+                let cx = x as f64 + 0.5;
+                let mut lambda = [0.0; 3];
+                let mut inside = true;
+                for k in 0..3 {
+                    let e = edge(edges[k].0, edges[k].1, cx, cy);
+                    if e < 0.0 || (e == 0.0 && !bias[k]) {
+                        inside = false;
+                        break;
+                    }
+                    lambda[order[k]] = (e / area) as f32;
+                }
+                if inside {
+                    let i = y as usize * self.width as usize + x as usize;
+                    self.fragment(draw_state, i, || shade(lambda));
+                }
+            }
+        }
+    }
+
+    /// Applies stencil test and blending to a single pixel.
+    fn fragment<S>(&mut self, draw_state: &DrawState, i: usize, shade: S)
+    where
+        S: FnOnce() -> Color,
+    {
+        match draw_state.stencil {
+            None => {}
+            Some(Stencil::Clip(val)) => {
+                self.stencil[i] = val;
+                return;
+            }
+            Some(Stencil::Increment) => {
+                self.stencil[i] = self.stencil[i].saturating_add(1);
+                return;
+            }
+            Some(Stencil::Inside(val)) if self.stencil[i] != val => return,
+            Some(Stencil::Outside(val)) if self.stencil[i] == val => return,
+            Some(Stencil::Inside(_)) | Some(Stencil::Outside(_)) => {}
+        }
+
+        let src = shade();
+        let px = &mut self.pixels[i * 4..i * 4 + 4];
+        let inv_255 = 1.0 / 255.0;
+        let dst = [
+            px[0] as f32 * inv_255,
+            px[1] as f32 * inv_255,
+            px[2] as f32 * inv_255,
+            px[3] as f32 * inv_255,
+        ];
+        let out = blend(draw_state.blend, src, dst);
+        for (px, c) in px.iter_mut().zip(&out) {
+            *px = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+    }
+}
+
+/// Blends source color with destination color.
+fn blend(blend: Option<Blend>, src: Color, dst: Color) -> Color {
+    let rgb =
+        |f: &dyn Fn(f32, f32) -> f32| [f(src[0], dst[0]), f(src[1], dst[1]), f(src[2], dst[2])];
+    let sa = src[3];
+    let (c, a) = match blend {
+        None => ([src[0], src[1], src[2]], sa),
+        Some(Blend::Alpha) => (rgb(&|s, d| s * sa + d * (1.0 - sa)), sa + dst[3]),
+        Some(Blend::Add) => (rgb(&|s, d| s + d), sa + dst[3]),
+        Some(Blend::Lighter) => (rgb(&|s, d| s * sa + d), dst[3]),
+        Some(Blend::Multiply) => (rgb(&|s, d| s * d), sa * dst[3]),
+        Some(Blend::Invert) => (rgb(&|s, d| s * (1.0 - d)), dst[3]),
+    };
+    [c[0], c[1], c[2], a]
+}
+
+/// Interpolates texture coordinates using barycentric coordinates.
+#[inline(always)]
+fn interpolate_uv(lambda: [f32; 3], uv: &[[f32; 2]]) -> [f32; 2] {
+    [
+        lambda[0] * uv[0][0] + lambda[1] * uv[1][0] + lambda[2] * uv[2][0],
+        lambda[0] * uv[0][1] + lambda[1] * uv[1][1] + lambda[2] * uv[2][1],
+    ]
+}
+
+/// Interpolates colors using barycentric coordinates.
+#[inline(always)]
+fn interpolate_color(lambda: [f32; 3], c: &[Color]) -> Color {
+    let mut res = [0.0; 4];
+    for (i, res) in res.iter_mut().enumerate() {
+        *res = lambda[0] * c[0][i] + lambda[1] * c[1][i] + lambda[2] * c[2][i];
+    }
+    res
+}
+
+impl Graphics for Canvas {
+    type Texture = Texture;
+
+    fn clear_color(&mut self, color: Color) {
+        let mut rgba = [0; 4];
+        for (c, v) in rgba.iter_mut().zip(&color) {
+            *c = (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+        for px in self.pixels.chunks_exact_mut(4) {
+            px.copy_from_slice(&rgba);
+        }
+    }
+
+    fn clear_stencil(&mut self, value: u8) {
+        for s in &mut self.stencil {
+            *s = value;
+        }
+    }
+
+    fn tri_list<F>(&mut self, draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+        f(&mut |vertices: &[[f32; 2]]| {
+            for tri in vertices.chunks_exact(3) {
+                self.fill_triangle(draw_state, tri, |_| *color);
+            }
+        });
+    }
+
+    fn tri_list_c<F>(&mut self, draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        f(&mut |vertices: &[[f32; 2]], colors: &[[f32; 4]]| {
+            for (tri, c) in vertices.chunks_exact(3).zip(colors.chunks_exact(3)) {
+                self.fill_triangle(draw_state, tri, |lambda| interpolate_color(lambda, c));
+            }
+        });
+    }
+
+    fn tri_list_uv<F>(
+        &mut self,
+        draw_state: &DrawState,
+        color: &[f32; 4],
+        texture: &Texture,
+        mut f: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+        f(&mut |vertices: &[[f32; 2]], texture_coords: &[[f32; 2]]| {
+            for (tri, uv) in vertices.chunks_exact(3).zip(texture_coords.chunks_exact(3)) {
+                self.fill_triangle(draw_state, tri, |lambda| {
+                    let t = texture.sample(interpolate_uv(lambda, uv));
+                    [
+                        t[0] * color[0],
+                        t[1] * color[1],
+                        t[2] * color[2],
+                        t[3] * color[3],
+                    ]
+                });
+            }
+        });
+    }
+
+    fn tri_list_uv_c<F>(&mut self, draw_state: &DrawState, texture: &Texture, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+        f(
+            &mut |vertices: &[[f32; 2]], texture_coords: &[[f32; 2]], colors: &[[f32; 4]]| {
+                for ((tri, uv), c) in vertices
+                    .chunks_exact(3)
+                    .zip(texture_coords.chunks_exact(3))
+                    .zip(colors.chunks_exact(3))
+                {
+                    self.fill_triangle(draw_state, tri, |lambda| {
+                        let t = texture.sample(interpolate_uv(lambda, uv));
+                        let color = interpolate_color(lambda, c);
+                        [
+                            t[0] * color[0],
+                            t[1] * color[1],
+                            t[2] * color[2],
+                            t[3] * color[3],
+                        ]
+                    });
+                }
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Context, Image, Rectangle};
+
+    #[test]
+    fn test_rectangle() {
+        let mut canvas = Canvas::new(10, 10);
+        let c = Context::new_abs(10.0, 10.0);
+        crate::clear([0.0, 0.0, 0.0, 1.0], &mut canvas);
+        Rectangle::new([1.0, 0.0, 0.0, 0.5]).draw(
+            [2.0, 2.0, 4.0, 4.0],
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(1, 1), [0, 0, 0, 255]);
+        assert_eq!(canvas.pixel(2, 2), [128, 0, 0, 255]);
+        // Shared diagonal edge is only blended once.
+        assert_eq!(canvas.pixel(3, 3), [128, 0, 0, 255]);
+        assert_eq!(canvas.pixel(5, 5), [128, 0, 0, 255]);
+        assert_eq!(canvas.pixel(6, 6), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_scissor_and_stencil() {
+        let mut canvas = Canvas::new(10, 10);
+        let c = Context::new_abs(10.0, 10.0);
+        let white = [1.0; 4];
+        let rect = [0.0, 0.0, 10.0, 10.0];
+        Rectangle::new(white).draw(
+            [0.0, 0.0, 5.0, 10.0],
+            &DrawState::new_clip(),
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(2, 2), [0; 4]);
+        assert_eq!(canvas.stencil(2, 2), 255);
+        Rectangle::new(white).draw(rect, &DrawState::new_inside(), c.transform, &mut canvas);
+        assert_eq!(canvas.pixel(2, 2), [255; 4]);
+        assert_eq!(canvas.pixel(7, 2), [0; 4]);
+
+        canvas.clear_stencil(0);
+        canvas.clear_color([0.0; 4]);
+        let ds = DrawState::default().scissor([1, 1, 2, 2]);
+        Rectangle::new(white).draw(rect, &ds, c.transform, &mut canvas);
+        assert_eq!(canvas.pixel(0, 0), [0; 4]);
+        assert_eq!(canvas.pixel(1, 1), [255; 4]);
+        assert_eq!(canvas.pixel(2, 2), [255; 4]);
+        assert_eq!(canvas.pixel(3, 3), [0; 4]);
+    }
+
+    #[test]
+    fn test_texture() {
+        let pixels = vec![
+            255, 0, 0, 255, 0, 255, 0, 255, //
+            0, 0, 255, 255, 255, 255, 255, 255,
+        ];
+        let texture = Texture::from_rgba8(2, 2, pixels).unwrap();
+        let mut canvas = Canvas::new(4, 4);
+        let c = Context::new_abs(4.0, 4.0);
+        Image::new().rect([0.0, 0.0, 4.0, 4.0]).draw(
+            &texture,
+            &c.draw_state,
+            c.transform,
+            &mut canvas,
+        );
+        assert_eq!(canvas.pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(3, 0), [0, 255, 0, 255]);
+        assert_eq!(canvas.pixel(0, 3), [0, 0, 255, 255]);
+        assert_eq!(canvas.pixel(3, 3), [255; 4]);
+    }
+}
@@ -0,0 +1,218 @@
+//! Draw sprites
+//!
+//! A `Sprite` is an image placed with a position, rotation and scale
+//! around a pivot point, the anchor.
+//! The anchor is relative to the size of the sprite,
+//! so `[0.5, 0.5]` is the center and `[0.0, 0.0]` is the upper left corner.
+//!
+//! ```
+//! use graphics::{software::{Canvas, Texture}, Context, Sprite};
+//!
+//! let texture = Texture::from_rgba8(2, 2, vec![255; 16]).unwrap();
+//! let c = Context::new_abs(100.0, 100.0);
+//! let mut canvas = Canvas::new(100, 100);
+//! Sprite::new()
+//!     .position([50.0, 50.0])
+//!     .rotation(0.5)
+//!     .scale([10.0, 10.0])
+//!     .flip_x(true)
+//!     .draw(&texture, &c.draw_state, c.transform, &mut canvas);
+//! ```
+
+use crate::{
+    math::{Matrix2d, Scalar, Vec2d},
+    types::{Color, SourceRectangle},
+    DrawState, Graphics, Image, ImageSize, Transformed,
+};
+
+/// An image with position, pivot, rotation, scale and flipping
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sprite {
+    /// The color multiplied with the texture
+    pub color: Option<Color>,
+    /// The image source rectangle
+    pub source_rectangle: Option<SourceRectangle>,
+    /// The position of the anchor
+    pub position: Vec2d,
+    /// The pivot point, relative to the size of the sprite
+    pub anchor: Vec2d,
+    /// The rotation around the anchor, in radians
+    pub rotation: Scalar,
+    /// The scale around the anchor
+    pub scale: Vec2d,
+    /// Whether the image is mirrored horizontally
+    pub flip_x: bool,
+    /// Whether the image is mirrored vertically
+    pub flip_y: bool,
+}
+
+impl Sprite {
+    /// Creates a new sprite anchored at the center.
+    pub fn new() -> Sprite {
+        Sprite {
+            color: None,
+            source_rectangle: None,
+            position: [0.0; 2],
+            anchor: [0.5; 2],
+            rotation: 0.0,
+            scale: [1.0; 2],
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    /// Sets color.
+    pub fn color(mut self, value: Color) -> Self {
+        self.color = Some(value);
+        self
+    }
+
+    /// Sets optional color.
+    pub fn maybe_color(mut self, value: Option<Color>) -> Self {
+        self.color = value;
+        self
+    }
+
+    /// Sets source rectangle.
+    pub fn src_rect(mut self, value: SourceRectangle) -> Self {
+        self.source_rectangle = Some(value);
+        self
+    }
+
+    /// Sets optional source rectangle.
+    pub fn maybe_src_rect(mut self, value: Option<SourceRectangle>) -> Self {
+        self.source_rectangle = value;
+        self
+    }
+
+    /// Sets position.
+    pub fn position(mut self, value: Vec2d) -> Self {
+        self.position = value;
+        self
+    }
+
+    /// Sets anchor.
+    pub fn anchor(mut self, value: Vec2d) -> Self {
+        self.anchor = value;
+        self
+    }
+
+    /// Sets rotation in radians.
+    pub fn rotation(mut self, value: Scalar) -> Self {
+        self.rotation = value;
+        self
+    }
+
+    /// Sets scale.
+    pub fn scale(mut self, value: Vec2d) -> Self {
+        self.scale = value;
+        self
+    }
+
+    /// Sets horizontal flipping.
+    pub fn flip_x(mut self, value: bool) -> Self {
+        self.flip_x = value;
+        self
+    }
+
+    /// Sets vertical flipping.
+    pub fn flip_y(mut self, value: bool) -> Self {
+        self.flip_y = value;
+        self
+    }
+
+    /// Returns the transform from sprite coordinates,
+    /// where the image covers `[0.0, 0.0, w, h]` of the source rectangle.
+    ///
+    /// Flipping mirrors the image in place, so it does not move the anchor.
+    pub fn transform(&self, size: Vec2d, transform: Matrix2d) -> Matrix2d {
+        let (w, h) = (size[0], size[1]);
+        let mut transform = transform
+            .trans_pos(self.position)
+            .rot_rad(self.rotation)
+            .scale_pos(self.scale)
+            .trans(-self.anchor[0] * w, -self.anchor[1] * h);
+        if self.flip_x {
+            transform = transform.trans(w, 0.0).flip_h();
+        }
+        if self.flip_y {
+            transform = transform.trans(0.0, h).flip_v();
+        }
+        transform
+    }
+
+    /// Draws sprite.
+    pub fn draw<G>(
+        &self,
+        texture: &<G as Graphics>::Texture,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        G: Graphics,
+    {
+        let src = self.source_rectangle.unwrap_or({
+            let (w, h) = texture.get_size();
+            [0.0, 0.0, w as Scalar, h as Scalar]
+        });
+        let transform = self.transform([src[2], src[3]], transform);
+        Image::new()
+            .maybe_color(self.color)
+            .src_rect(src)
+            .rect([0.0, 0.0, src[2], src[3]])
+            .draw(texture, draw_state, transform, g);
+    }
+}
+
+impl Default for Sprite {
+    fn default() -> Self {
+        Sprite::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        radians::Radians,
+        software::{Canvas, Texture},
+        Context,
+    };
+
+    #[test]
+    fn test_sprite() {
+        // Red left pixel, blue right pixel.
+        let texture = Texture::from_rgba8(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let c = Context::new_abs(20.0, 20.0);
+        let sprite = Sprite::new().position([10.0, 10.0]).scale([4.0, 4.0]);
+        let draw = |sprite: Sprite| {
+            let mut canvas = Canvas::new(20, 20);
+            sprite.draw(&texture, &c.draw_state, c.transform, &mut canvas);
+            canvas
+        };
+
+        // Covers 6..14 horizontally and 8..12 vertically.
+        let canvas = draw(sprite);
+        assert_eq!(canvas.pixel(7, 10), red);
+        assert_eq!(canvas.pixel(12, 10), blue);
+        assert_eq!(canvas.pixel(5, 10), [0; 4]);
+        assert_eq!(canvas.pixel(10, 7), [0; 4]);
+
+        let canvas = draw(sprite.flip_x(true));
+        assert_eq!(canvas.pixel(7, 10), blue);
+        assert_eq!(canvas.pixel(12, 10), red);
+
+        // Rotates clockwise on screen around the center.
+        let canvas = draw(sprite.rotation(<Scalar as Radians>::_90()));
+        assert_eq!(canvas.pixel(10, 7), red);
+        assert_eq!(canvas.pixel(10, 12), blue);
+
+        // Flipping with the anchor in the corner keeps the sprite in place.
+        let canvas = draw(sprite.anchor([0.0, 0.0]).flip_x(true));
+        assert_eq!(canvas.pixel(11, 11), blue);
+        assert_eq!(canvas.pixel(16, 11), red);
+        assert_eq!(canvas.pixel(8, 11), [0; 4]);
+    }
+}
@@ -0,0 +1,646 @@
+//! SVG export back-end.
+//!
+//! Writes the triangles passed to the back-end as an SVG document.
+//! This can be used to export drawings made with this library into reports
+//! or other vector graphics documents.
+//!
+//! Triangles of the same draw call are merged into a single SVG path.
+//! SVG has no per-vertex colors, so triangles with individual vertex colors
+//! are filled with the average color of their vertices.
+//!
+//! Textures use the in-memory texture of the software rasterizer,
+//! such that the image data can be embedded in the document as PNG.
+//!
+//! `DrawState` is mapped to SVG where possible:
+//!
+//! - Scissor rectangles become clip paths
+//! - Shapes drawn with `Stencil::Clip` or `Stencil::Increment` are not visible,
+//!   but are used as clip path for `Stencil::Inside` and as mask for `Stencil::Outside`.
+//!   The stencil value is ignored.
+//! - Blend modes are mapped to `mix-blend-mode`
+//!
+//! ```
+//! use graphics::{svg::Svg, Context, Rectangle};
+//!
+//! let mut svg = Svg::new(100, 100);
+//! let c = Context::new_abs(100.0, 100.0);
+//! Rectangle::new([1.0, 0.0, 0.0, 1.0]).draw(
+//!     [0.0, 0.0, 50.0, 50.0],
+//!     &c.draw_state,
+//!     c.transform,
+//!     &mut svg,
+//! );
+//! let document = svg.to_string();
+//! assert!(document.starts_with("<svg"));
+//! ```
+
+use std::fmt::{self, Write};
+
+use crate::{
+    draw_state::{Blend, Stencil},
+    software::Texture,
+    types::Color,
+    DrawState, Graphics, ImageSize,
+};
+
+/// A back-end that builds an SVG document.
+pub struct Svg {
+    width: u32,
+    height: u32,
+    defs: String,
+    body: String,
+    next_id: usize,
+    // Path data of shapes drawn to the stencil buffer.
+    stencil: String,
+    // Clip path and mask ids for the current stencil shapes.
+    stencil_ids: (Option<usize>, Option<usize>),
+    scissor: Option<([u32; 4], usize)>,
+    textures: Vec<(Texture, usize)>,
+    filters: Vec<(Color, usize)>,
+}
+
+impl Svg {
+    /// Creates a new SVG document with size in pixels.
+    pub fn new(width: u32, height: u32) -> Svg {
+        Svg {
+            width,
+            height,
+            defs: String::new(),
+            body: String::new(),
+            next_id: 0,
+            stencil: String::new(),
+            stencil_ids: (None, None),
+            scissor: None,
+            textures: vec![],
+            filters: vec![],
+        }
+    }
+
+    /// Returns the width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Transforms from normalized device coordinates to pixels.
+    fn pos(&self, v: [f32; 2]) -> [f32; 2] {
+        [
+            (v[0] + 1.0) * 0.5 * self.width as f32,
+            (1.0 - v[1]) * 0.5 * self.height as f32,
+        ]
+    }
+
+    /// Appends a triangle to path data.
+    fn push_triangle(&self, d: &mut String, tri: &[[f32; 2]]) {
+        let (a, mut b, mut c) = (self.pos(tri[0]), self.pos(tri[1]), self.pos(tri[2]));
+        // Use same orientation for all triangles,
+        // such that overlapping triangles are filled.
+        if (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]) < 0.0 {
+            std::mem::swap(&mut b, &mut c);
+        }
+        let _ = write!(
+            d,
+            "M{} {}L{} {}L{} {}Z",
+            num(a[0]),
+            num(a[1]),
+            num(b[0]),
+            num(b[1]),
+            num(c[0]),
+            num(c[1])
+        );
+    }
+
+    /// Writes an element with draw state applied.
+    ///
+    /// Elements drawn to the stencil buffer are stored as path data `d`.
+    fn element(&mut self, draw_state: &DrawState, element: &str, d: &str) {
+        match draw_state.stencil {
+            Some(Stencil::Clip(_)) | Some(Stencil::Increment) => {
+                self.stencil.push_str(d);
+                self.stencil_ids = (None, None);
+                return;
+            }
+            _ => {}
+        }
+
+        let mut attributes = String::new();
+        if let Some(scissor) = draw_state.scissor {
+            let id = match self.scissor {
+                Some((s, id)) if s == scissor => id,
+                _ => {
+                    let id = self.id();
+                    let _ = write!(
+                        self.defs,
+                        "<clipPath id=\"c{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>",
+                        id, scissor[0], scissor[1], scissor[2], scissor[3]
+                    );
+                    self.scissor = Some((scissor, id));
+                    id
+                }
+            };
+            let _ = write!(attributes, " clip-path=\"url(#c{})\"", id);
+        }
+        match draw_state.blend {
+            Some(Blend::Add) | Some(Blend::Lighter) => {
+                attributes.push_str(" style=\"mix-blend-mode:plus-lighter\"")
+            }
+            Some(Blend::Multiply) => attributes.push_str(" style=\"mix-blend-mode:multiply\""),
+            Some(Blend::Invert) => attributes.push_str(" style=\"mix-blend-mode:difference\""),
+            Some(Blend::Alpha) | None => {}
+        }
+
+        let mut inner = String::new();
+        match draw_state.stencil {
+            Some(Stencil::Inside(_)) => {
+                let id = match self.stencil_ids.0 {
+                    Some(id) => id,
+                    None => {
+                        let id = self.id();
+                        let _ = write!(
+                            self.defs,
+                            "<clipPath id=\"s{}\"><path d=\"{}\"/></clipPath>",
+                            id, self.stencil
+                        );
+                        self.stencil_ids.0 = Some(id);
+                        id
+                    }
+                };
+                let _ = write!(inner, " clip-path=\"url(#s{})\"", id);
+            }
+            Some(Stencil::Outside(_)) => {
+                let id = match self.stencil_ids.1 {
+                    Some(id) => id,
+                    None => {
+                        let id = self.id();
+                        let _ = write!(
+                            self.defs,
+                            "<mask id=\"m{}\" maskUnits=\"userSpaceOnUse\"><rect width=\"{}\" height=\"{}\" fill=\"white\"/><path d=\"{}\" fill=\"black\"/></mask>",
+                            id, self.width, self.height, self.stencil
+                        );
+                        self.stencil_ids.1 = Some(id);
+                        id
+                    }
+                };
+                let _ = write!(inner, " mask=\"url(#m{})\"", id);
+            }
+            _ => {}
+        }
+
+        if attributes.is_empty() && inner.is_empty() {
+            self.body.push_str(element);
+        } else {
+            let _ = write!(
+                self.body,
+                "<g{}><g{}>{}</g></g>",
+                attributes, inner, element
+            );
+        }
+    }
+
+    /// Writes a path filled with a color.
+    fn fill(&mut self, draw_state: &DrawState, color: &Color, d: &str) {
+        if d.is_empty() {
+            return;
+        }
+        let element = format!("<path d=\"{}\"{}/>", d, fill(color));
+        self.element(draw_state, &element, d);
+    }
+
+    /// Returns id of embedded texture.
+    fn texture(&mut self, texture: &Texture) -> usize {
+        if let Some(&(_, id)) = self.textures.iter().find(|(t, _)| t == texture) {
+            return id;
+        }
+        let id = self.id();
+        let (w, h) = texture.get_size();
+        // `xlink:href` is for SVG 1.1 renderers, `href` for SVG 2.
+        let _ = write!(
+            self.defs,
+            "<image id=\"t{id}\" width=\"{w}\" height=\"{h}\" \
+             href=\"data:image/png;base64,{data}\" xlink:href=\"data:image/png;base64,{data}\"/>",
+            id = id,
+            w = w,
+            h = h,
+            data = base64(&png(w, h, texture.pixels()))
+        );
+        self.textures.push((texture.clone(), id));
+        id
+    }
+
+    /// Returns id of filter that multiplies with a color.
+    fn filter(&mut self, color: &Color) -> Option<usize> {
+        if *color == [1.0; 4] {
+            return None;
+        }
+        if let Some(&(_, id)) = self.filters.iter().find(|(c, _)| c == color) {
+            return Some(id);
+        }
+        let id = self.id();
+        let _ = write!(
+            self.defs,
+            "<filter id=\"f{}\"><feColorMatrix type=\"matrix\" values=\"{} 0 0 0 0 0 {} 0 0 0 0 0 {} 0 0 0 0 0 {} 0\"/></filter>",
+            id,
+            num(color[0]),
+            num(color[1]),
+            num(color[2]),
+            num(color[3])
+        );
+        self.filters.push((*color, id));
+        Some(id)
+    }
+
+    /// Writes textured triangles.
+    ///
+    /// Consecutive triangles that share the same mapping from texture to
+    /// the document are drawn as a single image clipped by the triangles.
+    fn textured(
+        &mut self,
+        draw_state: &DrawState,
+        texture: &Texture,
+        vertices: &[[f32; 2]],
+        texture_coords: &[[f32; 2]],
+        colors: &mut dyn FnMut(usize) -> Color,
+    ) {
+        let tex = self.texture(texture);
+        let (w, h) = texture.get_size();
+        let mut current: Option<([f32; 6], Color, String)> = None;
+        for (i, (tri, uv)) in vertices
+            .chunks_exact(3)
+            .zip(texture_coords.chunks_exact(3))
+            .enumerate()
+        {
+            let color = colors(i);
+            let p = [self.pos(tri[0]), self.pos(tri[1]), self.pos(tri[2])];
+            let t = [
+                [uv[0][0] * w as f32, uv[0][1] * h as f32],
+                [uv[1][0] * w as f32, uv[1][1] * h as f32],
+                [uv[2][0] * w as f32, uv[2][1] * h as f32],
+            ];
+            let m = match affine(t, p) {
+                None => continue,
+                Some(m) => m,
+            };
+            let mut d = String::new();
+            self.push_triangle(&mut d, tri);
+            match current {
+                Some((cm, cc, ref mut cd))
+                    if cc == color && cm.iter().zip(&m).all(|(a, b)| (a - b).abs() < 1e-3) =>
+                {
+                    cd.push_str(&d);
+                }
+                _ => {
+                    if let Some((cm, cc, cd)) = current.take() {
+                        self.image(draw_state, tex, cm, &cc, &cd);
+                    }
+                    current = Some((m, color, d));
+                }
+            }
+        }
+        if let Some((cm, cc, cd)) = current {
+            self.image(draw_state, tex, cm, &cc, &cd);
+        }
+    }
+
+    /// Writes an image transformed by a matrix and clipped by path data.
+    fn image(&mut self, draw_state: &DrawState, tex: usize, m: [f32; 6], color: &Color, d: &str) {
+        let id = self.id();
+        let _ = write!(
+            self.defs,
+            "<clipPath id=\"c{}\"><path d=\"{}\"/></clipPath>",
+            id, d
+        );
+        let filter = match self.filter(color) {
+            Some(f) => format!(" filter=\"url(#f{})\"", f),
+            None => String::new(),
+        };
+        let element = format!(
+            "<g clip-path=\"url(#c{0})\"><use href=\"#t{1}\" xlink:href=\"#t{1}\" \
+             transform=\"matrix({2} {3} {4} {5} {6} {7})\"{8}/></g>",
+            id,
+            tex,
+            num(m[0]),
+            num(m[1]),
+            num(m[2]),
+            num(m[3]),
+            num(m[4]),
+            num(m[5]),
+            filter
+        );
+        self.element(draw_state, &element, d);
+    }
+}
+
+impl fmt::Display for Svg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = self.width,
+            h = self.height
+        )?;
+        if !self.defs.is_empty() {
+            write!(f, "<defs>{}</defs>", self.defs)?;
+        }
+        write!(f, "{}</svg>", self.body)
+    }
+}
+
+impl Graphics for Svg {
+    type Texture = Texture;
+
+    fn clear_color(&mut self, color: Color) {
+        // Everything drawn so far is covered.
+        self.body.clear();
+        let _ = write!(
+            self.body,
+            "<rect width=\"{}\" height=\"{}\"{}/>",
+            self.width,
+            self.height,
+            fill(&color)
+        );
+    }
+
+    fn clear_stencil(&mut self, _value: u8) {
+        self.stencil.clear();
+        self.stencil_ids = (None, None);
+    }
+
+    fn tri_list<F>(&mut self, draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+        let mut d = String::new();
+        f(&mut |vertices: &[[f32; 2]]| {
+            for tri in vertices.chunks_exact(3) {
+                self.push_triangle(&mut d, tri);
+            }
+        });
+        self.fill(draw_state, color, &d);
+    }
+
+    fn tri_list_c<F>(&mut self, draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        let mut paths: Vec<(Color, String)> = vec![];
+        f(&mut |vertices: &[[f32; 2]], colors: &[[f32; 4]]| {
+            for (tri, c) in vertices.chunks_exact(3).zip(colors.chunks_exact(3)) {
+                let color = average(c);
+                match paths.last_mut() {
+                    Some((last, d)) if *last == color => self.push_triangle(d, tri),
+                    _ => {
+                        let mut d = String::new();
+                        self.push_triangle(&mut d, tri);
+                        paths.push((color, d));
+                    }
+                }
+            }
+        });
+        for (color, d) in &paths {
+            self.fill(draw_state, color, d);
+        }
+    }
+
+    fn tri_list_uv<F>(
+        &mut self,
+        draw_state: &DrawState,
+        color: &[f32; 4],
+        texture: &Texture,
+        mut f: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+        f(&mut |vertices: &[[f32; 2]], texture_coords: &[[f32; 2]]| {
+            self.textured(draw_state, texture, vertices, texture_coords, &mut |_| {
+                *color
+            });
+        });
+    }
+
+    fn tri_list_uv_c<F>(&mut self, draw_state: &DrawState, texture: &Texture, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+        f(
+            &mut |vertices: &[[f32; 2]], texture_coords: &[[f32; 2]], colors: &[[f32; 4]]| {
+                self.textured(draw_state, texture, vertices, texture_coords, &mut |i| {
+                    average(&colors[i * 3..i * 3 + 3])
+                });
+            },
+        );
+    }
+}
+
+/// Formats a number with at most two decimals.
+fn num(v: f32) -> f32 {
+    (v * 100.0).round() / 100.0
+}
+
+/// Returns fill attributes for a color.
+fn fill(color: &Color) -> String {
+    let c = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut res = format!(
+        " fill=\"#{:02x}{:02x}{:02x}\"",
+        c(color[0]),
+        c(color[1]),
+        c(color[2])
+    );
+    if color[3] < 1.0 {
+        let _ = write!(res, " fill-opacity=\"{}\"", num(color[3].max(0.0)));
+    }
+    res
+}
+
+/// Computes the average color of a triangle.
+fn average(colors: &[[f32; 4]]) -> Color {
+    let mut res = [0.0; 4];
+    for (i, res) in res.iter_mut().enumerate() {
+        *res = (colors[0][i] + colors[1][i] + colors[2][i]) / 3.0;
+    }
+    res
+}
+
+/// Computes the affine matrix `[a, b, c, d, e, f]` that maps triangle `src` to `dst`.
+///
+/// Returns `None` if the source triangle has no area.
+fn affine(src: [[f32; 2]; 3], dst: [[f32; 2]; 3]) -> Option<[f32; 6]> {
+    let (s0, s1, s2) = (src[0], src[1], src[2]);
+    let (u1, v1) = (s1[0] - s0[0], s1[1] - s0[1]);
+    let (u2, v2) = (s2[0] - s0[0], s2[1] - s0[1]);
+    let det = u1 * v2 - u2 * v1;
+    if det.abs() < 1e-9 {
+        return None;
+    }
+    let (x1, y1) = (dst[1][0] - dst[0][0], dst[1][1] - dst[0][1]);
+    let (x2, y2) = (dst[2][0] - dst[0][0], dst[2][1] - dst[0][1]);
+    // Solve `[a c; b d] * [u1 u2; v1 v2] = [x1 x2; y1 y2]`.
+    let a = (x1 * v2 - x2 * v1) / det;
+    let c = (x2 * u1 - x1 * u2) / det;
+    let b = (y1 * v2 - y2 * v1) / det;
+    let d = (y2 * u1 - y1 * u2) / det;
+    let e = dst[0][0] - a * s0[0] - c * s0[1];
+    let f = dst[0][1] - b * s0[0] - d * s0[1];
+    Some([a, b, c, d, e, f])
+}
+
+/// Encodes RGBA8 pixels as an uncompressed PNG image.
+fn png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    fn chunk(out: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = out.len();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        let crc = crc32(&out[start..]);
+        out.extend_from_slice(&crc.to_be_bytes());
+    }
+
+    // Each row starts with filter type 0.
+    let row = width as usize * 4;
+    let mut raw = Vec::with_capacity((row + 1) * height as usize);
+    for y in 0..height as usize {
+        raw.push(0);
+        raw.extend_from_slice(&pixels[y * row..(y + 1) * row]);
+    }
+
+    // Zlib stream with stored blocks.
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        zlib.push(last as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in &raw {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    zlib.extend_from_slice(&((b << 16) | a).to_be_bytes());
+
+    let mut header = vec![];
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, color type RGBA, no interlace.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut out = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &zlib);
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[allow(clippy::manual_div_ceil)] // `usize::div_ceil` requires Rust 1.73.
+fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut res = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(TABLE[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Context, Image, Rectangle};
+
+    #[test]
+    fn test_rectangle() {
+        let mut svg = Svg::new(10, 10);
+        let c = Context::new_abs(10.0, 10.0);
+        Rectangle::new([1.0, 0.0, 0.0, 0.5]).draw(
+            [0.0, 0.0, 5.0, 5.0],
+            &c.draw_state,
+            c.transform,
+            &mut svg,
+        );
+        assert_eq!(
+            svg.to_string(),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"10\" height=\"10\" viewBox=\"0 0 10 10\">\
+             <path d=\"M0 0L5 0L0 5ZM5 0L5 5L0 5Z\" fill=\"#ff0000\" fill-opacity=\"0.5\"/></svg>"
+        );
+    }
+
+    #[test]
+    fn test_stencil() {
+        let mut svg = Svg::new(10, 10);
+        let c = Context::new_abs(10.0, 10.0);
+        let rect = [0.0, 0.0, 5.0, 5.0];
+        Rectangle::new([1.0; 4]).draw(rect, &DrawState::new_clip(), c.transform, &mut svg);
+        Rectangle::new([1.0; 4]).draw(rect, &DrawState::new_inside(), c.transform, &mut svg);
+        let document = svg.to_string();
+        assert!(document.contains("<clipPath id=\"s0\">"));
+        assert!(document.contains("clip-path=\"url(#s0)\""));
+    }
+
+    #[test]
+    fn test_image() {
+        let mut svg = Svg::new(10, 10);
+        let c = Context::new_abs(10.0, 10.0);
+        let texture = Texture::from_rgba8(1, 1, vec![255; 4]).unwrap();
+        Image::new().rect([0.0, 0.0, 4.0, 4.0]).draw(
+            &texture,
+            &c.draw_state,
+            c.transform,
+            &mut svg,
+        );
+        let document = svg.to_string();
+        assert!(document.contains("href=\"data:image/png;base64,iVBORw0KGgo"));
+        assert!(document.contains("xlink:href=\"data:image/png;base64,iVBORw0KGgo"));
+        assert!(document.contains("<use href=\"#t0\" xlink:href=\"#t0\""));
+        // Both triangles share the same mapping.
+        assert_eq!(document.matches("<use").count(), 1);
+        assert!(document.contains("matrix(4 0 0 4 0 0)"));
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+}
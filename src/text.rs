@@ -1,12 +1,13 @@
 //! Draw text
 
 use crate::{
-    character::CharacterCache,
+    character::{Character, CharacterCache},
     color,
     math::Matrix2d,
     math::Vec2d,
+    text_layout::{Layout, TextLayout},
     types::{Color, FontSize},
-    DrawState, Graphics, Image, Transformed,
+    DrawState, Graphics, Image, ImageSize, Transformed,
 };
 
 /// Renders text
@@ -75,34 +76,123 @@ impl Text {
         C: CharacterCache,
         G: Graphics<Texture = <C as CharacterCache>::Texture>,
     {
-        let mut image = Image::new_color(self.color);
-
         let mut x = 0.0;
         let mut y = 0.0;
+        let mut prev = None;
         for ch in text.chars() {
-            let character = cache.character(self.font_size, ch)?;
-            let mut ch_x = x + character.left();
-            let mut ch_y = y - character.top();
-            if self.round {
-                ch_x = ch_x.round();
-                ch_y = ch_y.round();
+            if let Some(prev) = prev {
+                x += cache.kerning(self.font_size, prev, ch)?;
             }
-            image = image.src_rect([
+            prev = Some(ch);
+            let character = cache.character(self.font_size, ch)?;
+            self.draw_glyph(&character, [x, y], draw_state, transform, g);
+            x += character.advance_width();
+            y += character.advance_height();
+        }
+
+        Ok(())
+    }
+
+    /// Draws laid out text with a character cache.
+    ///
+    /// The font size of the layout is used.
+    pub fn draw_layout<C, G>(
+        &self,
+        layout: &Layout,
+        cache: &mut C,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) -> Result<(), C::Error>
+    where
+        C: CharacterCache,
+        G: Graphics<Texture = <C as CharacterCache>::Texture>,
+    {
+        for glyph in layout.lines.iter().flat_map(|line| &line.glyphs) {
+            let character = cache.character(layout.font_size, glyph.ch)?;
+            self.draw_glyph(&character, glyph.pos, draw_state, transform, g);
+        }
+
+        Ok(())
+    }
+
+    /// Draws a character with the pen at a position on the baseline.
+    fn draw_glyph<T, G>(
+        &self,
+        character: &Character<'_, T>,
+        pos: Vec2d,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) where
+        T: ImageSize,
+        G: Graphics<Texture = T>,
+    {
+        let mut ch_x = pos[0] + character.left();
+        let mut ch_y = pos[1] - character.top();
+        if self.round {
+            ch_x = ch_x.round();
+            ch_y = ch_y.round();
+        }
+        Image::new_color(self.color)
+            .src_rect([
                 character.atlas_offset[0],
                 character.atlas_offset[1],
                 character.atlas_size[0],
                 character.atlas_size[1],
-            ]);
-            image.draw(
+            ])
+            .draw(
                 character.texture,
                 draw_state,
                 transform.trans(ch_x, ch_y),
                 g,
             );
-            x += character.advance_width();
-            y += character.advance_height();
-        }
+    }
 
-        Ok(())
+    /// Draws text broken into lines with a character cache.
+    pub fn draw_wrapped<C, G>(
+        &self,
+        text: &str,
+        settings: &TextLayout,
+        cache: &mut C,
+        draw_state: &DrawState,
+        transform: Matrix2d,
+        g: &mut G,
+    ) -> Result<(), C::Error>
+    where
+        C: CharacterCache,
+        G: Graphics<Texture = <C as CharacterCache>::Texture>,
+    {
+        let layout = settings.layout(text, self.font_size, cache)?;
+        self.draw_layout(&layout, cache, draw_state, transform, g)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{software::Canvas, text_layout::test::MonoCache, Context};
+
+    #[test]
+    fn test_draw_wrapped() {
+        let mut cache = MonoCache::new();
+        let c = Context::new_abs(40.0, 40.0);
+        let mut canvas = Canvas::new(40, 40);
+        let settings = TextLayout::new().max_width(30.0).line_spacing(2.0);
+        Text::new_color([1.0; 4], 10)
+            .draw_wrapped(
+                "ab cd",
+                &settings,
+                &mut cache,
+                &c.draw_state,
+                c.transform.trans(0.0, 10.0),
+                &mut canvas,
+            )
+            .unwrap();
+        // Each character is a box from x + 1 to x + 9, above the baseline.
+        assert_eq!(canvas.pixel(5, 5), [255; 4]);
+        assert_eq!(canvas.pixel(25, 5), [0; 4]);
+        assert_eq!(canvas.pixel(15, 25), [255; 4]);
+        assert_eq!(canvas.pixel(15, 15), [0; 4]);
     }
 }
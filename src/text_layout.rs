@@ -0,0 +1,414 @@
+//! Multi-line text layout.
+//!
+//! `TextLayout` breaks text into lines and positions the characters,
+//! using the advance widths and kerning from a `CharacterCache`.
+//! The result is drawn with `Text::draw_layout`,
+//! or in one step with `Text::draw_wrapped`.
+//!
+//! - Lines break at `\n`
+//! - With a maximum width, lines break at whitespace,
+//!   or between characters when a word is too long
+//! - Lines are aligned left, centered, right or justified
+//!
+//! The origin is on the baseline of the first line,
+//! like for `Text::draw`.
+
+use std::ops::Range;
+
+use crate::{
+    character::CharacterCache,
+    math::{Scalar, Vec2d},
+    types::FontSize,
+};
+
+/// The horizontal alignment of lines.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Align {
+    /// Aligns lines to the left.
+    Left,
+    /// Centers lines.
+    Center,
+    /// Aligns lines to the right.
+    Right,
+    /// Stretches whitespace to fill the maximum width.
+    ///
+    /// The last line of each paragraph is aligned to the left.
+    Justify,
+}
+
+/// A character positioned by the layout.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Glyph {
+    /// The character.
+    pub ch: char,
+    /// The position of the character on the baseline.
+    pub pos: Vec2d,
+}
+
+/// A line of laid out text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    /// The byte range of the line in the text.
+    pub range: Range<usize>,
+    /// The width of the line, before justification.
+    pub width: Scalar,
+    /// The characters of the line.
+    pub glyphs: Vec<Glyph>,
+}
+
+/// Laid out text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layout {
+    /// The font size used for the layout.
+    pub font_size: FontSize,
+    /// The lines, from top to bottom.
+    pub lines: Vec<Line>,
+    /// The width and height of the text block.
+    ///
+    /// The width is the maximum width if there is one,
+    /// and the width of the longest line otherwise.
+    /// The height is the number of lines times the line height.
+    pub size: Vec2d,
+}
+
+/// Settings for breaking text into lines and aligning them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextLayout {
+    /// The maximum width of lines.
+    pub max_width: Option<Scalar>,
+    /// The alignment of lines.
+    pub align: Align,
+    /// The distance between baselines, relative to the font size.
+    pub line_spacing: Scalar,
+}
+
+impl TextLayout {
+    /// Creates a new left aligned layout without maximum width.
+    pub fn new() -> TextLayout {
+        TextLayout {
+            max_width: None,
+            align: Align::Left,
+            line_spacing: 1.2,
+        }
+    }
+
+    /// Sets maximum width.
+    pub fn max_width(mut self, value: Scalar) -> Self {
+        self.max_width = Some(value);
+        self
+    }
+
+    /// Sets optional maximum width.
+    pub fn maybe_max_width(mut self, value: Option<Scalar>) -> Self {
+        self.max_width = value;
+        self
+    }
+
+    /// Sets alignment.
+    pub fn align(mut self, value: Align) -> Self {
+        self.align = value;
+        self
+    }
+
+    /// Sets line spacing, relative to the font size.
+    pub fn line_spacing(mut self, value: Scalar) -> Self {
+        self.line_spacing = value;
+        self
+    }
+
+    /// Returns the distance between baselines for a font size.
+    pub fn line_height(&self, font_size: FontSize) -> Scalar {
+        font_size as Scalar * self.line_spacing
+    }
+
+    /// Lays out text with a character cache.
+    pub fn layout<C>(
+        &self,
+        text: &str,
+        font_size: FontSize,
+        cache: &mut C,
+    ) -> Result<Layout, C::Error>
+    where
+        C: CharacterCache,
+    {
+        // Byte offset, character, advance width and kerning with the previous character.
+        let mut chars: Vec<(usize, char, Scalar, Scalar)> = vec![];
+        // Lines as ranges of `chars`, whether they end a paragraph,
+        // and the byte offset of the paragraph.
+        let mut ranges: Vec<(Range<usize>, bool, usize)> = vec![];
+        let mut start = 0;
+        for paragraph in text.split('\n') {
+            let offset = paragraph.as_ptr() as usize - text.as_ptr() as usize;
+            let paragraph = paragraph.strip_suffix('\r').unwrap_or(paragraph);
+            let mut prev = None;
+            for (i, ch) in paragraph.char_indices() {
+                let kerning = match prev {
+                    Some(prev) => cache.kerning(font_size, prev, ch)?,
+                    None => 0.0,
+                };
+                prev = Some(ch);
+                let advance = cache.character(font_size, ch)?.advance_width();
+                chars.push((offset + i, ch, advance, kerning));
+            }
+            let end = chars.len();
+            let first = ranges.len();
+            break_lines(&chars, start..end, self.max_width, &mut ranges);
+            for line in &mut ranges[first..] {
+                line.2 = offset;
+            }
+            start = end;
+        }
+
+        let line_height = self.line_height(font_size);
+        let widths: Vec<Scalar> = ranges
+            .iter()
+            .map(|(range, _, _)| line_width(&chars[range.clone()]))
+            .collect();
+        let block_width = self
+            .max_width
+            .unwrap_or_else(|| widths.iter().cloned().fold(0.0, Scalar::max));
+        let mut lines = Vec::with_capacity(ranges.len());
+        for (k, ((range, last, offset), &width)) in ranges.iter().zip(&widths).enumerate() {
+            let line = &chars[range.clone()];
+            let free = block_width - width;
+            let spaces = line.iter().filter(|c| c.1.is_whitespace()).count();
+            let (mut x, spacing) = match self.align {
+                Align::Left => (0.0, 0.0),
+                Align::Center => (0.5 * free, 0.0),
+                Align::Right => (free, 0.0),
+                Align::Justify if !last && spaces > 0 && free > 0.0 => {
+                    (0.0, free / spaces as Scalar)
+                }
+                Align::Justify => (0.0, 0.0),
+            };
+            let y = k as Scalar * line_height;
+            let glyphs = line
+                .iter()
+                .enumerate()
+                .map(|(i, &(_, ch, advance, kerning))| {
+                    if i > 0 {
+                        x += kerning;
+                    }
+                    let glyph = Glyph { ch, pos: [x, y] };
+                    x += advance + if ch.is_whitespace() { spacing } else { 0.0 };
+                    glyph
+                })
+                .collect();
+            let byte_range = match (line.first(), line.last()) {
+                (Some(first), Some(last)) => first.0..last.0 + last.1.len_utf8(),
+                // Empty lines are at the start of the paragraph.
+                _ => *offset..*offset,
+            };
+            lines.push(Line {
+                range: byte_range,
+                width,
+                glyphs,
+            });
+        }
+        let size = [block_width, lines.len() as Scalar * line_height];
+        Ok(Layout {
+            font_size,
+            lines,
+            size,
+        })
+    }
+}
+
+impl Default for TextLayout {
+    fn default() -> Self {
+        TextLayout::new()
+    }
+}
+
+/// Returns the width of a line, without kerning before the first character.
+fn line_width(line: &[(usize, char, Scalar, Scalar)]) -> Scalar {
+    line.iter()
+        .enumerate()
+        .map(|(i, c)| if i > 0 { c.2 + c.3 } else { c.2 })
+        .sum()
+}
+
+/// Breaks a paragraph into lines.
+///
+/// The byte offsets of the lines are set by the caller.
+/// Lines break at the last space that fits,
+/// or before the first character that does not fit.
+/// Spaces at line breaks are removed.
+fn break_lines(
+    chars: &[(usize, char, Scalar, Scalar)],
+    paragraph: Range<usize>,
+    max_width: Option<Scalar>,
+    lines: &mut Vec<(Range<usize>, bool, usize)>,
+) {
+    let is_space = |i: usize| chars[i].1.is_whitespace();
+    let Range { mut start, end } = paragraph;
+    loop {
+        let mut width = 0.0;
+        let mut i = start;
+        let mut space = None;
+        while i < end {
+            let advance = if i > start {
+                chars[i].2 + chars[i].3
+            } else {
+                chars[i].2
+            };
+            if is_space(i) {
+                if i > start {
+                    space = Some(i);
+                }
+            } else if let Some(max_width) = max_width {
+                if i > start && width + advance > max_width {
+                    break;
+                }
+            }
+            width += advance;
+            i += 1;
+        }
+        let (mut line_end, mut next) = match space {
+            Some(space) if i < end => (space, space),
+            _ => (i, i),
+        };
+        while line_end > start && is_space(line_end - 1) {
+            line_end -= 1;
+        }
+        while next < end && is_space(next) {
+            next += 1;
+        }
+        lines.push((start..line_end, next >= end, 0));
+        if next >= end {
+            break;
+        }
+        start = next;
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{character::Character, software::Texture};
+
+    /// A character cache where characters are 10 units wide.
+    ///
+    /// Glyphs are white boxes of 8x10 on the baseline,
+    /// except 'g' which is 8x14 and hangs 2 units left and 4 units down.
+    /// The pairs "AV" and "VA" are kerned by -3 units.
+    pub(crate) struct MonoCache {
+        pub texture: Texture,
+    }
+
+    impl MonoCache {
+        pub fn new() -> MonoCache {
+            MonoCache {
+                texture: Texture::from_rgba8(1, 1, vec![255; 4]).unwrap(),
+            }
+        }
+    }
+
+    impl CharacterCache for MonoCache {
+        type Texture = Texture;
+        type Error = ();
+
+        fn character(&mut self, _: FontSize, ch: char) -> Result<Character<'_, Texture>, ()> {
+            let (offset, size) = match ch {
+                'g' => ([-2.0, 10.0], [8.0, 14.0]),
+                _ if ch.is_whitespace() => ([1.0, 10.0], [0.0; 2]),
+                _ => ([1.0, 10.0], [8.0, 10.0]),
+            };
+            Ok(Character {
+                offset,
+                advance_size: [10.0, 0.0],
+                atlas_offset: [0.0; 2],
+                atlas_size: size,
+                texture: &self.texture,
+                is_invalid: false,
+            })
+        }
+
+        fn kerning(&mut self, _: FontSize, first: char, second: char) -> Result<Scalar, ()> {
+            match (first, second) {
+                ('A', 'V') | ('V', 'A') => Ok(-3.0),
+                _ => Ok(0.0),
+            }
+        }
+    }
+
+    fn lines(layout: &Layout, text: &str) -> Vec<String> {
+        layout
+            .lines
+            .iter()
+            .map(|line| text[line.range.clone()].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_wrap() {
+        let mut cache = MonoCache::new();
+        let text = "hello world  foo\n\nabcdefghijkl";
+        let layout = TextLayout::new()
+            .max_width(100.0)
+            .layout(text, 10, &mut cache)
+            .unwrap();
+        assert_eq!(
+            lines(&layout, text),
+            vec!["hello", "world  foo", "", "abcdefghij", "kl"]
+        );
+        assert_eq!(layout.lines[1].width, 100.0);
+        assert_eq!(layout.lines[4].glyphs[1].pos, [10.0, 48.0]);
+        assert_eq!(layout.size, [100.0, 60.0]);
+
+        // Without maximum width, only new lines break.
+        let layout = TextLayout::new().layout(text, 10, &mut cache).unwrap();
+        assert_eq!(
+            lines(&layout, text),
+            vec!["hello world  foo", "", "abcdefghijkl"]
+        );
+        assert_eq!(layout.size[0], 160.0);
+    }
+
+    #[test]
+    fn test_align() {
+        let text = "aa bb cc\ndd";
+        let settings = TextLayout::new().max_width(60.0);
+        let layout = |align| {
+            settings
+                .align(align)
+                .layout(text, 10, &mut MonoCache::new())
+        };
+        let first_x = |layout: &Layout, line: usize| layout.lines[line].glyphs[0].pos[0];
+
+        let left = layout(Align::Left).unwrap();
+        assert_eq!(lines(&left, text), vec!["aa bb", "cc", "dd"]);
+        let right = layout(Align::Right).unwrap();
+        assert_eq!(first_x(&right, 1), 40.0);
+        let center = layout(Align::Center).unwrap();
+        assert_eq!(first_x(&center, 0), 5.0);
+        let justify = layout(Align::Justify).unwrap();
+        // The space is stretched, except on the last line of a paragraph.
+        assert_eq!(justify.lines[0].glyphs[3].pos[0], 40.0);
+        assert_eq!(first_x(&justify, 1), 0.0);
+        // Tabs break lines and are stretched like spaces.
+        let justify = settings
+            .align(Align::Justify)
+            .layout("aa\tbb cc", 10, &mut MonoCache::new())
+            .unwrap();
+        assert_eq!(justify.lines[0].glyphs[3].pos[0], 40.0);
+    }
+
+    #[test]
+    fn test_kerning() {
+        let mut cache = MonoCache::new();
+        let layout = TextLayout::new().layout("AVA", 10, &mut cache).unwrap();
+        let xs: Vec<Scalar> = layout.lines[0].glyphs.iter().map(|g| g.pos[0]).collect();
+        assert_eq!(xs, vec![0.0, 7.0, 14.0]);
+        assert_eq!(layout.size[0], 24.0);
+
+        // Kerning is not applied across line breaks.
+        let layout = TextLayout::new()
+            .max_width(20.0)
+            .layout("VAVA", 10, &mut cache)
+            .unwrap();
+        assert_eq!(lines(&layout, "VAVA"), vec!["VA", "VA"]);
+        assert_eq!(layout.lines[1].width, 17.0);
+        assert_eq!(layout.lines[1].glyphs[0].pos[0], 0.0);
+    }
+}
@@ -2,9 +2,11 @@
 use interpolation::lerp;
 
 use crate::{
-    math::{multiply, orient, translate, Matrix2d, Scalar, Vec2d},
+    line::{Dash, Join, Shape},
+    math::{inside_triangle, line_side, multiply, orient, translate, Matrix2d, Scalar, Vec2d},
+    polygon::FillRule,
     radians::Radians,
-    types::{Line, Polygon, Polygons, Radius, Rectangle, Resolution, SourceRectangle},
+    types::{Contours, Line, Polygon, Polygons, Radius, Rectangle, Resolution, SourceRectangle},
     ImageSize, BACK_END_MAX_VERTEX_COUNT as BUFFER_SIZE,
 };
 
@@ -55,18 +57,33 @@ pub fn with_ellipse_tri_list<F>(resolution: Resolution, m: Matrix2d, rect: Recta
 where
     F: FnMut(&[[f32; 2]]),
 {
+    stream_polygon_tri_list(m, ellipse_points(resolution, rect), f);
+}
+
+/// Streams an ellipse specified by a resolution as indexed triangles.
+///
+/// All triangles share the first vertex, which is only sent once per chunk.
+#[inline(always)]
+pub fn with_ellipse_tri_list_indexed<F>(resolution: Resolution, m: Matrix2d, rect: Rectangle, f: F)
+where
+    F: FnMut(&[[f32; 2]], &[u16]),
+{
+    stream_polygon_tri_list_indexed(m, ellipse_points(resolution, rect), f);
+}
+
+/// Returns the points of an ellipse specified by a resolution.
+pub(crate) fn ellipse_points(
+    resolution: Resolution,
+    rect: Rectangle,
+) -> impl Iterator<Item = Vec2d> {
     let (x, y, w, h) = (rect[0], rect[1], rect[2], rect[3]);
     let (cw, ch) = (0.5 * w, 0.5 * h);
     let (cx, cy) = (x + cw, y + ch);
     let n = resolution;
-    stream_polygon_tri_list(
-        m,
-        (0..n).map(|i| {
-            let angle = i as Scalar / n as Scalar * <Scalar as Radians>::_360();
-            [cx + angle.cos() * cw, cy + angle.sin() * ch]
-        }),
-        f,
-    );
+    (0..n).map(move |i| {
+        let angle = i as Scalar / n as Scalar * <Scalar as Radians>::_360();
+        [cx + angle.cos() * cw, cy + angle.sin() * ch]
+    })
 }
 
 /// Streams a round border line.
@@ -123,7 +140,6 @@ pub fn with_round_border_line_tri_list<F>(
 }
 
 /// Streams a round rectangle.
-#[allow(clippy::identity_op)] // Identity operations are used for readibility.
 #[inline(always)]
 pub fn with_round_rectangle_tri_list<F>(
     resolution_corner: Resolution,
@@ -134,71 +150,100 @@ pub fn with_round_rectangle_tri_list<F>(
 ) where
     F: FnMut(&[[f32; 2]]),
 {
+    stream_polygon_tri_list(
+        m,
+        round_rectangle_points(resolution_corner, rect, round_radius),
+        f,
+    );
+}
+
+/// Streams a round rectangle as indexed triangles.
+///
+/// All triangles share the first vertex, which is only sent once per chunk.
+#[inline(always)]
+pub fn with_round_rectangle_tri_list_indexed<F>(
+    resolution_corner: Resolution,
+    m: Matrix2d,
+    rect: Rectangle,
+    round_radius: Radius,
+    f: F,
+) where
+    F: FnMut(&[[f32; 2]], &[u16]),
+{
+    stream_polygon_tri_list_indexed(
+        m,
+        round_rectangle_points(resolution_corner, rect, round_radius),
+        f,
+    );
+}
+
+/// Returns the points of a round rectangle.
+#[allow(clippy::identity_op)] // Identity operations are used for readibility.
+pub(crate) fn round_rectangle_points(
+    resolution_corner: Resolution,
+    rect: Rectangle,
+    round_radius: Radius,
+) -> impl Iterator<Item = Vec2d> {
     use vecmath::traits::FromPrimitive;
 
     let (x, y, w, h) = (rect[0], rect[1], rect[2], rect[3]);
     let radius = round_radius;
     let n = resolution_corner * 4;
-    stream_polygon_tri_list(
-        m,
-        (0..n).map(|j| {
-            // Detect quarter circle from index.
-            // There is one quarter circle at each corner.
-            // Together they form a full circle if
-            // each side of rectangle is 2 times the radius.
-            match j {
-                j if j >= resolution_corner * 3 => {
-                    // Compute the angle to match start and end
-                    // point of quarter circle.
-                    // This requires an angle offset since this
-                    // is the last quarter.
-                    let angle: Scalar = (j - resolution_corner * 3) as Scalar
-                        / (resolution_corner - 1) as Scalar
-                        * <Scalar as Radians>::_90()
-                        + <Scalar as FromPrimitive>::from_f64(3.0) * <Scalar as Radians>::_90();
-                    // Set center of the circle to the last corner.
-                    let (cx, cy) = (x + w - radius, y + radius);
-                    [cx + angle.cos() * radius, cy + angle.sin() * radius]
-                }
-                j if j >= resolution_corner * 2 => {
-                    // Compute the angle to match start and end
-                    // point of quarter circle.
-                    // This requires an angle offset since
-                    // this is the second last quarter.
-                    let angle = (j - resolution_corner * 2) as Scalar
-                        / (resolution_corner - 1) as Scalar
-                        * <Scalar as Radians>::_90()
-                        + <Scalar as Radians>::_180();
-                    // Set center of the circle to the second last corner.
-                    let (cx, cy) = (x + radius, y + radius);
-                    [cx + angle.cos() * radius, cy + angle.sin() * radius]
-                }
-                j if j >= resolution_corner * 1 => {
-                    // Compute the angle to match start and end
-                    // point of quarter circle.
-                    // This requires an angle offset since
-                    // this is the second quarter.
-                    let angle = (j - resolution_corner) as Scalar
-                        / (resolution_corner - 1) as Scalar
-                        * <Scalar as Radians>::_90()
-                        + <Scalar as Radians>::_90();
-                    // Set center of the circle to the second corner.
-                    let (cx, cy) = (x + radius, y + h - radius);
-                    [cx + angle.cos() * radius, cy + angle.sin() * radius]
-                }
-                j => {
-                    // Compute the angle to match start and end
-                    // point of quarter circle.
-                    let angle = j as Scalar / (resolution_corner - 1) as Scalar
-                        * <Scalar as Radians>::_90();
-                    // Set center of the circle to the first corner.
-                    let (cx, cy) = (x + w - radius, y + h - radius);
-                    [cx + angle.cos() * radius, cy + angle.sin() * radius]
-                }
+    (0..n).map(move |j| {
+        // Detect quarter circle from index.
+        // There is one quarter circle at each corner.
+        // Together they form a full circle if
+        // each side of rectangle is 2 times the radius.
+        match j {
+            j if j >= resolution_corner * 3 => {
+                // Compute the angle to match start and end
+                // point of quarter circle.
+                // This requires an angle offset since this
+                // is the last quarter.
+                let angle: Scalar = (j - resolution_corner * 3) as Scalar
+                    / (resolution_corner - 1) as Scalar
+                    * <Scalar as Radians>::_90()
+                    + <Scalar as FromPrimitive>::from_f64(3.0) * <Scalar as Radians>::_90();
+                // Set center of the circle to the last corner.
+                let (cx, cy) = (x + w - radius, y + radius);
+                [cx + angle.cos() * radius, cy + angle.sin() * radius]
             }
-        }),
-        f,
-    );
+            j if j >= resolution_corner * 2 => {
+                // Compute the angle to match start and end
+                // point of quarter circle.
+                // This requires an angle offset since
+                // this is the second last quarter.
+                let angle = (j - resolution_corner * 2) as Scalar
+                    / (resolution_corner - 1) as Scalar
+                    * <Scalar as Radians>::_90()
+                    + <Scalar as Radians>::_180();
+                // Set center of the circle to the second last corner.
+                let (cx, cy) = (x + radius, y + radius);
+                [cx + angle.cos() * radius, cy + angle.sin() * radius]
+            }
+            j if j >= resolution_corner * 1 => {
+                // Compute the angle to match start and end
+                // point of quarter circle.
+                // This requires an angle offset since
+                // this is the second quarter.
+                let angle = (j - resolution_corner) as Scalar / (resolution_corner - 1) as Scalar
+                    * <Scalar as Radians>::_90()
+                    + <Scalar as Radians>::_90();
+                // Set center of the circle to the second corner.
+                let (cx, cy) = (x + radius, y + h - radius);
+                [cx + angle.cos() * radius, cy + angle.sin() * radius]
+            }
+            j => {
+                // Compute the angle to match start and end
+                // point of quarter circle.
+                let angle =
+                    j as Scalar / (resolution_corner - 1) as Scalar * <Scalar as Radians>::_90();
+                // Set center of the circle to the first corner.
+                let (cx, cy) = (x + w - radius, y + h - radius);
+                [cx + angle.cos() * radius, cy + angle.sin() * radius]
+            }
+        }
+    })
 }
 
 /// Streams a polygon into tri list.
@@ -271,6 +316,279 @@ where
     }
 }
 
+/// Streams a polygon into indexed tri list.
+///
+/// Works like `stream_polygon_tri_list`, but sends each vertex once per chunk.
+/// Each chunk contains the vertices and the indices of the triangles,
+/// where every 3 indices form a triangle.
+/// When the vertex buffer is full, the next chunk starts with
+/// the first and the last vertex of the previous chunk.
+pub fn stream_polygon_tri_list_indexed<E, F>(m: Matrix2d, mut polygon: E, mut f: F)
+where
+    E: Iterator<Item = Vec2d>,
+    F: FnMut(&[[f32; 2]], &[u16]),
+{
+    let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+    let mut indices: [u16; 3 * BUFFER_SIZE] = [0; 3 * BUFFER_SIZE];
+    let fp = match polygon.next() {
+        None => return,
+        Some(val) => val,
+    };
+    vertices[0] = [tx(m, fp[0], fp[1]), ty(m, fp[0], fp[1])];
+    let gp = match polygon.next() {
+        None => return,
+        Some(val) => val,
+    };
+    vertices[1] = [tx(m, gp[0], gp[1]), ty(m, gp[0], gp[1])];
+    // Number of vertices in buffer.
+    let mut n = 2;
+    // Number of indices in buffer.
+    let mut k = 0;
+    for p in polygon {
+        if n == BUFFER_SIZE {
+            // Send chunk and start over with the first and last vertex.
+            f(&vertices[0..n], &indices[0..k]);
+            vertices[1] = vertices[n - 1];
+            n = 2;
+            k = 0;
+        }
+        vertices[n] = [tx(m, p[0], p[1]), ty(m, p[0], p[1])];
+        indices[k] = 0;
+        indices[k + 1] = (n - 1) as u16;
+        indices[k + 2] = n as u16;
+        n += 1;
+        k += 3;
+    }
+
+    if k > 0 {
+        f(&vertices[0..n], &indices[0..k]);
+    }
+}
+
+/// Streams a polyline with joins and caps into tri list.
+///
+/// Points repeated in a row are skipped.
+/// An open polyline of a single point only draws the caps.
+/// The inner side of each corner is trimmed where the segments meet,
+/// so triangles do not overlap unless the segments are too short.
+///
+/// See stream_polygon_tri_list docs for detailed explanation.
+#[allow(clippy::too_many_arguments)]
+pub fn with_polyline_tri_list<F>(
+    m: Matrix2d,
+    points: Polygon<'_>,
+    closed: bool,
+    radius: Radius,
+    join: Join,
+    cap: Shape,
+    mut f: F,
+) where
+    F: FnMut(&[[f32; 2]]),
+{
+    let mut pts: Vec<Vec2d> = Vec::with_capacity(points.len());
+    for &p in points {
+        if pts.last() != Some(&p) {
+            pts.push(p);
+        }
+    }
+    if closed && pts.len() > 2 && pts[0] == pts[pts.len() - 1] {
+        pts.pop();
+    }
+    if pts.len() == 1 && !closed {
+        // Draw the caps of a zero length line.
+        pts.push(pts[0]);
+    }
+    let n = pts.len();
+    if n < 2 {
+        return;
+    }
+    let segments = if closed { n } else { n - 1 };
+    // Unit direction and length of each segment.
+    let dirs: Vec<(Vec2d, Scalar)> = (0..segments)
+        .map(|i| {
+            let (a, b) = (pts[i], pts[(i + 1) % n]);
+            let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+            let len = (dx * dx + dy * dy).sqrt();
+            if len > 0.0 {
+                ([dx / len, dy / len], len)
+            } else {
+                ([1.0, 0.0], 0.0)
+            }
+        })
+        .collect();
+    let offset = |p: Vec2d, v: Vec2d, s: Scalar| [p[0] + v[0] * s, p[1] + v[1] * s];
+    let normal = |d: Vec2d| [-d[1], d[0]];
+
+    let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+    let mut i = 0;
+    let mut triangle = |tri: [Vec2d; 3]| {
+        for p in &tri {
+            vertices[i] = [tx(m, p[0], p[1]), ty(m, p[0], p[1])];
+            i += 1;
+        }
+        if i + 3 > BUFFER_SIZE {
+            f(&vertices[0..i]);
+            i = 0;
+        }
+    };
+
+    // The [left, right] corners at the start and end of each segment.
+    let mut starts: Vec<[Vec2d; 2]> = Vec::with_capacity(segments);
+    let mut ends: Vec<[Vec2d; 2]> = Vec::with_capacity(segments);
+    for (j, &(d, _)) in dirs.iter().enumerate() {
+        let nv = normal(d);
+        let (a, b) = (pts[j], pts[(j + 1) % n]);
+        starts.push([offset(a, nv, radius), offset(a, nv, -radius)]);
+        ends.push([offset(b, nv, radius), offset(b, nv, -radius)]);
+    }
+
+    // Joins.
+    let joints = if closed { 0..n } else { 1..n - 1 };
+    for j in joints {
+        let s0 = (j + segments - 1) % segments;
+        let s1 = j % segments;
+        let p = pts[j];
+        let ((d0, l0), (d1, l1)) = (dirs[s0], dirs[s1]);
+        let (n0, n1) = (normal(d0), normal(d1));
+        let cross = d0[0] * d1[1] - d0[1] * d1[0];
+        let dot = d0[0] * d1[0] + d0[1] * d1[1];
+        if cross.abs() < 1e-9 && dot > 0.0 {
+            continue;
+        }
+        // The outer side is left when turning right.
+        let side = if cross > 0.0 { -1.0 } else { 1.0 };
+        let o0 = offset(p, n0, side * radius);
+        let o1 = offset(p, n1, side * radius);
+        // Cosine of half the angle between the outer normals.
+        let cos_half = (0.5 * (1.0 + dot)).max(0.0).sqrt();
+        let bisector = if cos_half > 1e-9 {
+            let b = [side * (n0[0] + n1[0]), side * (n0[1] + n1[1])];
+            let len = (b[0] * b[0] + b[1] * b[1]).sqrt();
+            Some([b[0] / len, b[1] / len])
+        } else {
+            None
+        };
+        // Trim the inner side where the offset edges meet.
+        let trim = radius * cross.abs() / (1.0 + dot);
+        let inner = match bisector {
+            Some(b) if trim <= 0.5 * l0.min(l1) => Some(offset(p, b, -radius / cos_half)),
+            _ => None,
+        };
+        let (k_out, k_in) = if side > 0.0 { (0, 1) } else { (1, 0) };
+        ends[s0][k_out] = o0;
+        starts[s1][k_out] = o1;
+        if let Some(q) = inner {
+            ends[s0][k_in] = q;
+            starts[s1][k_in] = q;
+        }
+        let pivot = inner.unwrap_or(p);
+        triangle([pivot, o0, o1]);
+        match join {
+            Join::Bevel => {}
+            Join::Miter(limit) => {
+                if let Some(b) = bisector {
+                    if 1.0 / cos_half <= limit {
+                        triangle([o0, offset(p, b, radius / cos_half), o1]);
+                    }
+                }
+            }
+            Join::Round => {
+                let a0 = (o0[1] - p[1]).atan2(o0[0] - p[0]);
+                let sweep = -side * dot.clamp(-1.0, 1.0).acos();
+                // Uses the same resolution as `Shape::Round` for a full circle.
+                let steps =
+                    ((sweep.abs() / <Scalar as Radians>::_360() * 64.0).ceil() as usize).max(1);
+                let mut prev = o0;
+                for k in 1..=steps {
+                    let next = if k == steps {
+                        o1
+                    } else {
+                        let angle = a0 + sweep * k as Scalar / steps as Scalar;
+                        [p[0] + radius * angle.cos(), p[1] + radius * angle.sin()]
+                    };
+                    if k > 1 {
+                        triangle([o0, prev, next]);
+                    }
+                    prev = next;
+                }
+            }
+        }
+    }
+
+    // Caps.
+    if !closed {
+        let last = segments - 1;
+        let caps = [
+            (0, pts[0], [-dirs[0].0[0], -dirs[0].0[1]], true),
+            (last, pts[n - 1], dirs[last].0, false),
+        ];
+        for &(s, p, out, start) in &caps {
+            let corners = if start { &mut starts[s] } else { &mut ends[s] };
+            match cap {
+                Shape::Square => {}
+                Shape::ProjectingSquare => {
+                    for c in corners.iter_mut() {
+                        *c = offset(*c, out, radius);
+                    }
+                }
+                _ => {
+                    // Half circle from the left to the right side.
+                    let res = cap.resolution();
+                    let nv = normal(if start { dirs[0].0 } else { dirs[last].0 });
+                    let mut prev = offset(p, nv, radius);
+                    for k in 1..res {
+                        let t = k as Scalar / (res - 1) as Scalar * <Scalar as Radians>::_180();
+                        let v = [
+                            nv[0] * t.cos() + out[0] * t.sin(),
+                            nv[1] * t.cos() + out[1] * t.sin(),
+                        ];
+                        let next = offset(p, v, radius);
+                        triangle([p, prev, next]);
+                        prev = next;
+                    }
+                }
+            }
+        }
+    }
+
+    // Segments.
+    for (a, b) in starts.iter().zip(&ends) {
+        triangle([a[0], a[1], b[1]]);
+        triangle([a[0], b[1], b[0]]);
+    }
+
+    if i > 0 {
+        f(&vertices[0..i]);
+    }
+}
+
+/// Streams a dashed polyline with joins and caps into tri list.
+///
+/// Each dash is drawn as an open polyline with caps.
+///
+/// See stream_polygon_tri_list docs for detailed explanation.
+#[allow(clippy::too_many_arguments)]
+pub fn with_dashed_polyline_tri_list<F>(
+    m: Matrix2d,
+    points: Polygon<'_>,
+    closed: bool,
+    radius: Radius,
+    dash: Dash,
+    join: Join,
+    cap: Shape,
+    mut f: F,
+) where
+    F: FnMut(&[[f32; 2]]),
+{
+    if dash.gap <= 0.0 {
+        with_polyline_tri_list(m, points, closed, radius, join, cap, f);
+        return;
+    }
+    for dash in dash.split(points, closed) {
+        with_polyline_tri_list(m, &dash, false, radius, join, cap, |vertices| f(vertices));
+    }
+}
+
 /// Streams an ellipse border specified by a resolution.
 #[inline(always)]
 pub fn with_ellipse_border_tri_list<F>(
@@ -583,6 +901,218 @@ where
     stream_polygon_tri_list(m, (0..polygon.len()).map(|i| polygon[i]), f);
 }
 
+/// Streams a polygon with multiple contours into tri list.
+///
+/// A horizontal line sweeps down the contours, keeping a list of
+/// the active edges crossing it sorted from left to right.
+/// The sweep stops at every vertex and every intersection between edges.
+/// Between two stops the active edges do not cross,
+/// so the filled parts form trapezoids between pairs of edges.
+/// Intersections only happen between neighbours in the active list,
+/// which is kept sorted with insertion sort since the order rarely changes.
+/// This takes `O(n log n + (n + k) a)` time for `n` edges, `k` intersections
+/// and at most `a` active edges at once.
+/// Coordinates that are NaN give garbage triangles, but do not panic.
+///
+/// See stream_polygon_tri_list docs for detailed explanation.
+pub fn with_contours_tri_list<F>(m: Matrix2d, contours: Contours<'_>, fill_rule: FillRule, mut f: F)
+where
+    F: FnMut(&[[f32; 2]]),
+{
+    // Edges as `([x0, y0, x1, y1], winding)` with `y0 < y1`.
+    let mut edges: Vec<(Line, i32)> = vec![];
+    for contour in contours {
+        let n = contour.len();
+        for i in 0..n {
+            let (a, b) = (contour[i], contour[(i + 1) % n]);
+            if a[1] < b[1] {
+                edges.push(([a[0], a[1], b[0], b[1]], 1));
+            } else if a[1] > b[1] {
+                edges.push(([b[0], b[1], a[0], a[1]], -1));
+            }
+        }
+    }
+    edges.sort_by(|(a, _), (b, _)| a[1].total_cmp(&b[1]));
+    let x_at = |e: &Line, y: Scalar| {
+        if y <= e[1] {
+            e[0]
+        } else if y >= e[3] {
+            e[2]
+        } else {
+            e[0] + (e[2] - e[0]) * (y - e[1]) / (e[3] - e[1])
+        }
+    };
+    let slope = |e: &Line| (e[2] - e[0]) / (e[3] - e[1]);
+
+    let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+    let mut i = 0;
+    let mut active: Vec<(Line, i32)> = vec![];
+    let mut next = 0;
+    let mut y0 = match edges.first() {
+        None => return,
+        Some((e, _)) => e[1],
+    };
+    loop {
+        active.retain(|(e, _)| e[3] > y0);
+        while next < edges.len() && edges[next].0[1] <= y0 {
+            active.push(edges[next]);
+            next += 1;
+        }
+        // Sorts by position on the sweep line, then by direction below it.
+        for j in 1..active.len() {
+            let mut k = j;
+            while k > 0 && {
+                let (a, b) = (&active[k - 1].0, &active[k].0);
+                x_at(a, y0)
+                    .total_cmp(&x_at(b, y0))
+                    .then(slope(a).total_cmp(&slope(b)))
+                    .is_gt()
+            } {
+                active.swap(k - 1, k);
+                k -= 1;
+            }
+        }
+
+        // The next stop is the closest start, end or intersection.
+        let mut y1 = edges.get(next).map_or(Scalar::INFINITY, |(e, _)| e[1]);
+        for (e, _) in &active {
+            y1 = y1.min(e[3]);
+        }
+        for pair in active.windows(2) {
+            let (a, b) = (&pair[0].0, &pair[1].0);
+            let end = a[3].min(b[3]);
+            // The horizontal distance between the edges changes linearly.
+            let d0 = x_at(b, y0) - x_at(a, y0);
+            let d1 = x_at(b, end) - x_at(a, end);
+            if d0 >= 0.0 && d1 < 0.0 {
+                let y = y0 + (end - y0) * d0 / (d0 - d1);
+                if y > y0 {
+                    y1 = y1.min(y);
+                }
+            }
+        }
+        if y1 == Scalar::INFINITY || y1.is_nan() {
+            break;
+        }
+
+        let mut winding = 0;
+        let mut left: Option<&Line> = None;
+        for (e, dir) in &active {
+            winding += dir;
+            let inside = match fill_rule {
+                FillRule::NonZero => winding != 0,
+                FillRule::EvenOdd => winding % 2 != 0,
+            };
+            match left {
+                None if inside => left = Some(e),
+                Some(l) if !inside => {
+                    let quad = [
+                        [x_at(l, y0), y0],
+                        [x_at(e, y0), y0],
+                        [x_at(e, y1), y1],
+                        [x_at(l, y1), y1],
+                    ];
+                    for &k in &[0, 1, 2, 0, 2, 3] {
+                        let p = quad[k];
+                        vertices[i] = [tx(m, p[0], p[1]), ty(m, p[0], p[1])];
+                        i += 1;
+                    }
+                    if i + 6 > BUFFER_SIZE {
+                        f(&vertices[0..i]);
+                        i = 0;
+                    }
+                    left = None;
+                }
+                _ => {}
+            }
+        }
+        y0 = y1;
+    }
+
+    if i > 0 {
+        f(&vertices[0..i]);
+    }
+}
+
+/// Streams a simple polygon into tri list using ear clipping.
+///
+/// Unlike `with_polygon_tri_list`, this works for concave polygons,
+/// as long as the polygon does not intersect itself.
+/// Each ear test checks all remaining vertices,
+/// so this takes `O(n^3)` time in the worst case in the number of vertices,
+/// and close to `O(n^2)` when ears are found quickly.
+///
+/// See stream_polygon_tri_list docs for detailed explanation.
+pub fn with_polygon_ear_clipping_tri_list<F>(m: Matrix2d, polygon: Polygon<'_>, mut f: F)
+where
+    F: FnMut(&[[f32; 2]]),
+{
+    let n = polygon.len();
+    if n < 3 {
+        return;
+    }
+    // Twice the signed area tells the orientation of the polygon.
+    let orientation: Scalar = (0..n)
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum();
+    // Positive for corners turning the same direction as the polygon.
+    let turn = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (polygon[a], polygon[b], polygon[c]);
+        line_side([a[0], a[1], b[0], b[1]], c) * orientation
+    };
+
+    let mut vertices: [[f32; 2]; BUFFER_SIZE] = [[0.0; 2]; BUFFER_SIZE];
+    let mut i = 0;
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut j = 0;
+    // Number of vertices visited without finding an ear.
+    let mut visited = 0;
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let (a, b, c) = (
+            remaining[(j + len - 1) % len],
+            remaining[j % len],
+            remaining[(j + 1) % len],
+        );
+        let triangle = [polygon[a], polygon[b], polygon[c]];
+        // An ear is a convex corner with no reflex vertices inside.
+        // Gives up when no ear is found, which happens for degenerate polygons.
+        let is_ear = visited >= len
+            || turn(a, b, c) >= 0.0
+                && !(0..len).any(|r| {
+                    let k = remaining[r];
+                    let p = polygon[k];
+                    !triangle.contains(&p)
+                        && turn(remaining[(r + len - 1) % len], k, remaining[(r + 1) % len]) <= 0.0
+                        && inside_triangle(triangle, p)
+                });
+        if is_ear {
+            for (ind, p) in triangle.iter().enumerate() {
+                vertices[i * 3 + ind] = [tx(m, p[0], p[1]), ty(m, p[0], p[1])];
+            }
+            i += 1;
+            if (i + 1) * 3 > BUFFER_SIZE {
+                f(&vertices[0..i * 3]);
+                i = 0;
+            }
+            remaining.remove(j % len);
+            j %= len - 1;
+            visited = 0;
+        } else {
+            j = (j + 1) % len;
+            visited += 1;
+        }
+    }
+    for (ind, &k) in remaining.iter().enumerate() {
+        let p = polygon[k];
+        vertices[i * 3 + ind] = [tx(m, p[0], p[1]), ty(m, p[0], p[1])];
+    }
+    f(&vertices[0..i * 3 + 3]);
+}
+
 /// Creates triangle list vertices from rectangle.
 #[inline(always)]
 pub fn rect_tri_list_xy(m: Matrix2d, rect: Rectangle) -> [[f32; 2]; 6] {
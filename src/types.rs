@@ -26,6 +26,12 @@ pub type Polygon<'a, T = Scalar> = &'a [Vec2d<T>];
 /// A slice of polygons.
 pub type Polygons<'a, T = Scalar> = &'a [Polygon<'a, T>];
 
+/// The contours of a polygon with holes: [outer, hole0, hole1, ...]
+///
+/// Whether an area is filled is decided by a fill rule,
+/// so contours may also overlap or intersect themselves.
+pub type Contours<'a, T = Scalar> = &'a [Polygon<'a, T>];
+
 /// The type used for radius.
 pub type Radius<T = Scalar> = T;
 